@@ -3,7 +3,7 @@
 
 extern crate test;
 
-use num_bigint::{BigInt, BigUint, RandBigInt};
+use num_bigint::{BarrettReducer, BigInt, BigUint, RandBigInt};
 use num_traits::{FromPrimitive, Num, One, Zero};
 use std::mem::replace;
 use test::Bencher;
@@ -396,6 +396,72 @@ fn modpow(b: &mut Bencher) {
     b.iter(|| base.modpow(&e, &m));
 }
 
+#[bench]
+fn barrett_pow_mod_even(b: &mut Bencher) {
+    let mut rng = get_rng();
+    let base = rng.gen_biguint(2048);
+    let e = rng.gen_biguint(2048);
+    let m = BigUint::from_str_radix(RFC3526_2048BIT_MODP_GROUP, 16).unwrap() - 1u32;
+    let reducer = BarrettReducer::new(&m);
+
+    b.iter(|| reducer.pow_mod(&base, &e));
+}
+
+fn barrett_reduce_bench(b: &mut Bencher, bits: u64, barrett: bool) {
+    let mut rng = get_rng();
+    let m = rng.gen_biguint(bits) << 1u8;
+    let x = rng.gen_biguint_below(&m) * rng.gen_biguint_below(&m);
+    let reducer = BarrettReducer::new(&m);
+
+    if barrett {
+        b.iter(|| reducer.reduce(&x));
+    } else {
+        b.iter(|| &x % &m);
+    }
+}
+
+#[bench]
+fn barrett_reduce_2048(b: &mut Bencher) {
+    barrett_reduce_bench(b, 2048, true);
+}
+
+#[bench]
+fn barrett_rem_2048(b: &mut Bencher) {
+    barrett_reduce_bench(b, 2048, false);
+}
+
+#[bench]
+fn barrett_reduce_16384(b: &mut Bencher) {
+    barrett_reduce_bench(b, 16384, true);
+}
+
+#[bench]
+fn barrett_rem_16384(b: &mut Bencher) {
+    barrett_reduce_bench(b, 16384, false);
+}
+
+#[bench]
+fn multi_modpow_even(b: &mut Bencher) {
+    let mut rng = get_rng();
+    let pairs: Vec<_> = (0..4)
+        .map(|_| (rng.gen_biguint(2048), rng.gen_biguint(2048)))
+        .collect();
+    let m = BigUint::from_str_radix(RFC3526_2048BIT_MODP_GROUP, 16).unwrap() - 1u32;
+
+    b.iter(|| BigUint::multi_modpow(&pairs, &m));
+}
+
+#[bench]
+fn multi_modpow(b: &mut Bencher) {
+    let mut rng = get_rng();
+    let pairs: Vec<_> = (0..4)
+        .map(|_| (rng.gen_biguint(2048), rng.gen_biguint(2048)))
+        .collect();
+    let m = BigUint::from_str_radix(RFC3526_2048BIT_MODP_GROUP, 16).unwrap();
+
+    b.iter(|| BigUint::multi_modpow(&pairs, &m));
+}
+
 #[bench]
 fn modpow_even(b: &mut Bencher) {
     let mut rng = get_rng();
@@ -21,6 +21,21 @@ fn factorial_mul_u32(b: &mut Bencher) {
     b.iter(|| (1u32..1000).fold(BigUint::one(), Mul::mul));
 }
 
+#[bench]
+fn factorial_1000(b: &mut Bencher) {
+    b.iter(|| BigUint::factorial(1000));
+}
+
+#[bench]
+fn factorial_100000(b: &mut Bencher) {
+    b.iter(|| BigUint::factorial(100_000));
+}
+
+#[bench]
+fn binomial_100000_50000(b: &mut Bencher) {
+    b.iter(|| BigUint::binomial(100_000, 50_000));
+}
+
 // The division test is inspired by this blog comparison:
 // <https://tiehuis.github.io/big-integers-in-zig#division-test-single-limb>
 
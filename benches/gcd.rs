@@ -53,7 +53,8 @@ fn gcd_euclid_4096(b: &mut Bencher) {
     bench(b, 4096, euclid);
 }
 
-// Integer for BigUint now uses Stein for gcd
+// Integer for BigUint now uses Stein for single-digit gcd,
+// then Lehmer and the half-GCD for larger inputs
 
 #[bench]
 fn gcd_stein_0064(b: &mut Bencher) {
@@ -74,3 +75,22 @@ fn gcd_stein_1024(b: &mut Bencher) {
 fn gcd_stein_4096(b: &mut Bencher) {
     bench(b, 4096, BigUint::gcd);
 }
+
+fn bench_big(b: &mut Bencher, bits: u64) {
+    // too large to cross-check against Euclid's algorithm each time
+    let mut rng = get_rng();
+    let x = rng.gen_biguint(bits);
+    let y = rng.gen_biguint(bits);
+
+    b.iter(|| x.gcd(&y));
+}
+
+#[bench]
+fn gcd_big_0065536(b: &mut Bencher) {
+    bench_big(b, 65536);
+}
+
+#[bench]
+fn gcd_big_1048576(b: &mut Bencher) {
+    bench_big(b, 1048576);
+}
@@ -201,6 +201,83 @@ mod biguint {
             check(x.clone(), 100);
         }
     }
+
+    #[test]
+    fn test_gen_prime() {
+        let mut rng = thread_rng();
+        for bits in (2..100).chain(vec![256, 512, 1024]) {
+            let p = rng.gen_prime(bits);
+            assert_eq!(p.bits(), bits);
+            assert!(p.bit(0));
+            assert!(p.is_probably_prime(20));
+        }
+    }
+
+    #[test]
+    fn test_gen_safe_prime() {
+        let mut rng = thread_rng();
+        for bits in (3..64).chain(vec![128, 256]) {
+            let p = rng.gen_safe_prime(bits);
+            assert_eq!(p.bits(), bits);
+            assert!(p.is_probably_prime(20));
+            assert!((&p >> 1u8).is_probably_prime(20));
+        }
+    }
+
+    #[test]
+    fn test_gen_prime_range() {
+        let mut rng = thread_rng();
+        for _ in 0..10 {
+            assert_eq!(
+                rng.gen_prime_range(&BigUint::from(24u32), &BigUint::from(30u32)),
+                BigUint::from(29u32)
+            );
+            assert_eq!(
+                rng.gen_prime_range(&BigUint::from(0u32), &BigUint::from(3u32)),
+                BigUint::from(2u32)
+            );
+        }
+
+        let l = BigUint::from(1u32) << 200;
+        let u = &l + (BigUint::from(1u32) << 100);
+        for _ in 0..10 {
+            let p = rng.gen_prime_range(&l, &u);
+            assert!(p >= l && p < u);
+            assert!(p.is_probably_prime(20));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_gen_prime_empty_range() {
+        let mut rng = thread_rng();
+        rng.gen_prime_range(&BigUint::from(24u32), &BigUint::from(29u32));
+    }
+
+    #[test]
+    fn test_gen_prime_seeded() {
+        // (bits, prime, safe prime), as drawn in turn from one generator
+        const EXPECTED: &[(u64, &str, &str)] = &[
+            (64, "10769634918422842477", "12787194356637163259"),
+            (
+                300,
+                "10395101731321255849195212643091580974392336086655966558351217129553772\
+                 21748107358114398489",
+                "18978409755951715464399273687353875655772837322367591478594096230093411\
+                 77950394301175003827",
+            ),
+        ];
+        use rand_xorshift::XorShiftRng;
+        let mut seed = <XorShiftRng as SeedableRng>::Seed::default();
+        for (i, x) in seed.iter_mut().enumerate() {
+            *x = (i as u8).wrapping_mul(191);
+        }
+        let mut rng = XorShiftRng::from_seed(seed);
+        for &(bits, prime, safe) in EXPECTED {
+            assert_eq!(rng.gen_prime(bits), prime.parse::<BigUint>().unwrap());
+            assert_eq!(rng.gen_safe_prime(bits), safe.parse::<BigUint>().unwrap());
+        }
+    }
 }
 
 mod bigint {
@@ -18,6 +18,7 @@ use self::Sign::{Minus, NoSign, Plus};
 
 use crate::big_digit::BigDigit;
 use crate::biguint::to_str_radix_reversed;
+use crate::biguint::{extended_gcd, jacobi};
 use crate::biguint::{BigUint, IntDigits, U32Digits, U64Digits};
 
 mod addition;
@@ -457,6 +458,13 @@ impl Integer for BigInt {
         (BigInt::from(gcd), BigInt::from(lcm))
     }
 
+    /// Greatest common divisor and Bézout coefficients.
+    #[inline]
+    fn extended_gcd(&self, other: &BigInt) -> num_integer::ExtendedGcd<BigInt> {
+        let (gcd, x, y) = extended_gcd(self, other);
+        num_integer::ExtendedGcd { gcd, x, y }
+    }
+
     /// Greatest common divisor, least common multiple, and Bézout coefficients.
     #[inline]
     fn extended_gcd_lcm(&self, other: &BigInt) -> (num_integer::ExtendedGcd<BigInt>, BigInt) {
@@ -975,6 +983,65 @@ impl BigInt {
         self.data.bits()
     }
 
+    /// Returns the logarithm of `self` with respect to `base`, rounded down.
+    ///
+    /// Panics if `self` is not positive or `base` is less than 2.
+    pub fn ilog(&self, base: &Self) -> u64 {
+        assert!(
+            base.is_positive() && base.data.bits() >= 2,
+            "base of integer logarithm must be at least 2"
+        );
+        self.checked_ilog(base)
+            .expect("argument of integer logarithm must be positive")
+    }
+
+    /// Returns the base 2 logarithm of `self`, rounded down.
+    ///
+    /// Panics if `self` is not positive.
+    pub fn ilog2(&self) -> u64 {
+        self.checked_ilog2()
+            .expect("argument of integer logarithm must be positive")
+    }
+
+    /// Returns the base 10 logarithm of `self`, rounded down.
+    ///
+    /// Panics if `self` is not positive.
+    pub fn ilog10(&self) -> u64 {
+        self.checked_ilog10()
+            .expect("argument of integer logarithm must be positive")
+    }
+
+    /// Returns the logarithm of `self` with respect to `base`, rounded down,
+    /// or `None` if `self` is not positive or `base` is less than 2 --
+    /// see [`BigUint::checked_ilog`](struct.BigUint.html#method.checked_ilog).
+    pub fn checked_ilog(&self, base: &Self) -> Option<u64> {
+        if self.is_positive() && base.is_positive() {
+            self.data.checked_ilog(&base.data)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the base 2 logarithm of `self`, rounded down, or `None` if `self`
+    /// is not positive.
+    pub fn checked_ilog2(&self) -> Option<u64> {
+        if self.is_positive() {
+            self.data.checked_ilog2()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the base 10 logarithm of `self`, rounded down, or `None` if `self`
+    /// is not positive.
+    pub fn checked_ilog10(&self) -> Option<u64> {
+        if self.is_positive() {
+            self.data.checked_ilog10()
+        } else {
+            None
+        }
+    }
+
     /// Converts this `BigInt` into a `BigUint`, if it's not negative.
     #[inline]
     pub fn to_biguint(&self) -> Option<BigUint> {
@@ -1013,6 +1080,11 @@ impl BigInt {
         Pow::pow(self, exponent)
     }
 
+    /// Returns `self * self`, which is never negative.
+    pub fn square(&self) -> Self {
+        BigInt::from(self.data.square())
+    }
+
     /// Returns `(self ^ exponent) mod modulus`
     ///
     /// Note that this rounds like `mod_floor`, not like the `%` operator,
@@ -1025,6 +1097,83 @@ impl BigInt {
         power::modpow(self, exponent, modulus)
     }
 
+    /// Returns the modular multiplicative inverse of `self` modulo `modulus`,
+    /// or `None` if `self` and `modulus` are not coprime.
+    ///
+    /// Like `modpow`, the result will be in the interval `[0, modulus)` for
+    /// `modulus > 0`, or in the interval `(modulus, 0]` for `modulus < 0`.
+    ///
+    /// Panics if the modulus is zero.
+    pub fn modinv(&self, modulus: &Self) -> Option<Self> {
+        let m = &modulus.data;
+        let mut inv = self.data.modinv(m)?;
+        if self.is_negative() && !inv.is_zero() {
+            inv = m - inv;
+        }
+        if modulus.is_negative() && !inv.is_zero() {
+            Some(BigInt::from_biguint(Minus, m - inv))
+        } else {
+            Some(BigInt::from(inv))
+        }
+    }
+
+    /// Returns the Jacobi symbol `(self / n)`, which is 0, 1 or -1.
+    ///
+    /// Panics if `n` is not odd and positive.
+    pub fn jacobi(&self, n: &Self) -> i8 {
+        assert!(
+            n.is_positive() && n.is_odd(),
+            "the Jacobi symbol requires an odd positive modulus"
+        );
+        jacobi(&self.mod_floor(n).data, &n.data)
+    }
+
+    /// Returns the Legendre symbol `(self / p)` for an odd prime `p`, which is 0 if
+    /// `p` divides `self`, 1 if `self` is a quadratic residue modulo `p`, or -1 otherwise.
+    ///
+    /// The primality of `p` isn't checked, so this is the Jacobi symbol for any odd `p`.
+    /// Panics if `p` is not odd and positive.
+    pub fn legendre(&self, p: &Self) -> i8 {
+        self.jacobi(p)
+    }
+
+    /// Returns the Kronecker symbol `(self / n)`, which extends the Jacobi symbol
+    /// to any `n`, and is 0, 1 or -1.
+    pub fn kronecker(&self, n: &Self) -> i8 {
+        if n.is_zero() {
+            return if self.data.is_one() { 1 } else { 0 };
+        }
+
+        // (a / -1) = -1 for negative a
+        let mut k = if n.is_negative() && self.is_negative() {
+            -1
+        } else {
+            1
+        };
+
+        // (a / 2) = 0 for even a, and -1 for a = 3 or 5 (mod 8)
+        let twos = n.data.trailing_zeros().unwrap();
+        if twos > 0 {
+            if self.is_even() {
+                return 0;
+            }
+            let low = self.data.iter_u32_digits().next().unwrap() & 7;
+            let a8 = if self.is_negative() { 8 - low } else { low };
+            if twos & 1 == 1 && (a8 == 3 || a8 == 5) {
+                k = -k;
+            }
+        }
+
+        let m = BigInt::from(&n.data >> twos);
+        k * jacobi(&self.mod_floor(&m).data, &m.data)
+    }
+
+    /// Returns `true` if `self` is positive and probably prime --
+    /// see [`BigUint::is_probably_prime`](struct.BigUint.html#method.is_probably_prime).
+    pub fn is_probably_prime(&self, rounds: usize) -> bool {
+        self.is_positive() && self.data.is_probably_prime(rounds)
+    }
+
     /// Returns the truncated principal square root of `self` --
     /// see [Roots::sqrt](https://docs.rs/num-integer/0.1/num_integer/trait.Roots.html#method.sqrt).
     pub fn sqrt(&self) -> Self {
@@ -7,7 +7,7 @@ use crate::BigInt;
 use crate::BigUint;
 use crate::Sign::*;
 
-use crate::biguint::biguint_from_vec;
+use crate::biguint::{biguint_from_vec, first_prime, Sieve};
 
 use num_integer::Integer;
 use num_traits::{ToPrimitive, Zero};
@@ -35,6 +35,26 @@ pub trait RandBigInt {
     /// bound is inclusive; the upper bound is exclusive. Fails when
     /// the upper bound is not greater than the lower bound.
     fn gen_bigint_range(&mut self, lbound: &BigInt, ubound: &BigInt) -> BigInt;
+
+    /// Generate a random probable prime of the given bit size, so the top bit
+    /// is always set. Fails when the bit size is less than 2.
+    ///
+    /// The result is odd, so 2 is never generated.
+    fn gen_prime(&mut self, bit_size: u64) -> BigUint;
+
+    /// Generate a random safe prime `p` of the given bit size, such that
+    /// `(p - 1) / 2` is also prime, and the top bit is always set. Fails when
+    /// the bit size is less than 3.
+    fn gen_safe_prime(&mut self, bit_size: u64) -> BigUint;
+
+    /// Generate a random probable prime within the given range. The lower
+    /// bound is inclusive; the upper bound is exclusive. Fails when the upper
+    /// bound is not greater than the lower bound, or there is no prime in
+    /// the range.
+    ///
+    /// This searches upward from a random point in the range, so primes that
+    /// follow a larger gap are more likely to be chosen.
+    fn gen_prime_range(&mut self, lbound: &BigUint, ubound: &BigUint) -> BigUint;
 }
 
 fn gen_bits<R: Rng + ?Sized>(rng: &mut R, data: &mut [u32], rem: u64) {
@@ -139,6 +159,46 @@ impl<R: Rng + ?Sized> RandBigInt for R {
             lbound + BigInt::from(self.gen_biguint_below(delta.magnitude()))
         }
     }
+
+    fn gen_prime(&mut self, bit_size: u64) -> BigUint {
+        assert!(bit_size >= 2, "a prime needs at least 2 bits");
+        loop {
+            let mut start = self.gen_biguint(bit_size);
+            start.set_bit(bit_size - 1, true);
+            start.set_bit(0, true);
+            let prime = Sieve::new(start, 2, false)
+                .take_while(|n| n.bits() == bit_size)
+                .find(|n| n.is_probably_prime(0));
+            if let Some(prime) = prime {
+                return prime;
+            }
+        }
+    }
+
+    fn gen_safe_prime(&mut self, bit_size: u64) -> BigUint {
+        assert!(bit_size >= 3, "a safe prime needs at least 3 bits");
+        loop {
+            // (p - 1) / 2 must be odd, so p = 3 mod 4
+            let mut start = self.gen_biguint(bit_size);
+            start.set_bit(bit_size - 1, true);
+            start.set_bit(1, true);
+            start.set_bit(0, true);
+            let prime = Sieve::new(start, 4, true)
+                .take_while(|p| p.bits() == bit_size)
+                .find(|p| (p >> 1u8).is_probably_prime(0) && p.is_probably_prime(0));
+            if let Some(prime) = prime {
+                return prime;
+            }
+        }
+    }
+
+    fn gen_prime_range(&mut self, lbound: &BigUint, ubound: &BigUint) -> BigUint {
+        assert!(*lbound < *ubound);
+        let start = self.gen_biguint_range(lbound, ubound);
+        first_prime(&start, ubound)
+            .or_else(|| first_prime(lbound, &start))
+            .expect("no prime in the range")
+    }
 }
 
 /// The back-end implementing rand's `UniformSampler` for `BigUint`.
@@ -18,12 +18,27 @@ mod division;
 mod multiplication;
 mod subtraction;
 
+mod barrett;
 mod bits;
+mod constant_time;
 mod convert;
+mod crt;
+mod discrete_log;
+mod factor;
+mod factorial;
+mod gcd;
 mod iter;
+mod log;
+mod lucas;
 mod monty;
+mod multi_exp;
+mod ntt;
+mod perfect_power;
 mod power;
+mod prime;
+mod secret;
 mod shift;
+mod sqrt_mod;
 
 #[cfg(any(feature = "quickcheck", feature = "arbitrary"))]
 mod arbitrary;
@@ -31,8 +46,16 @@ mod arbitrary;
 #[cfg(feature = "serde")]
 mod serde;
 
+pub use self::barrett::BarrettReducer;
+pub use self::constant_time::{ConstantTimeContext, FixedUint};
 pub(crate) use self::convert::to_str_radix_reversed;
+pub use self::crt::CrtBasis;
+pub use self::factor::{Factorization, Factorizer};
+pub(crate) use self::gcd::{extended_gcd, jacobi};
 pub use self::iter::{U32Digits, U64Digits};
+pub use self::monty::{MontgomeryContext, MontgomeryElement};
+#[cfg(feature = "rand")]
+pub(crate) use self::prime::{first_prime, Sieve};
 
 /// A big unsigned integer type.
 pub struct BigUint {
@@ -218,9 +241,10 @@ impl Integer for BigUint {
     /// The result is always positive.
     #[inline]
     fn gcd(&self, other: &Self) -> Self {
-        #[inline]
-        fn twos(x: &BigUint) -> u64 {
-            x.trailing_zeros().unwrap_or(0)
+        use self::gcd::twos;
+
+        if cmp::max(self.data.len(), other.data.len()) >= gcd::LEHMER_THRESHOLD {
+            return gcd::gcd(self, other);
         }
 
         // Stein's algorithm
@@ -494,7 +518,7 @@ impl Roots for BigUint {
         let guess = BigUint::one() << max_bits;
 
         fixpoint(guess, max_bits, move |s| {
-            let q = self / (s * s);
+            let q = self / s.square();
             let t = (s << 1) + q;
             t / 3u32
         })
@@ -842,6 +866,56 @@ impl BigUint {
         self.data.len() as u64 * u64::from(big_digit::BITS) - zeros
     }
 
+    /// Returns the logarithm of `self` with respect to `base`, rounded down.
+    ///
+    /// Panics if `self` is zero or `base` is less than 2.
+    pub fn ilog(&self, base: &Self) -> u64 {
+        assert!(
+            base.bits() >= 2,
+            "base of integer logarithm must be at least 2"
+        );
+        self.checked_ilog(base)
+            .expect("argument of integer logarithm must be positive")
+    }
+
+    /// Returns the base 2 logarithm of `self`, rounded down.
+    ///
+    /// Panics if `self` is zero.
+    pub fn ilog2(&self) -> u64 {
+        self.checked_ilog2()
+            .expect("argument of integer logarithm must be positive")
+    }
+
+    /// Returns the base 10 logarithm of `self`, rounded down, which is one less
+    /// than the number of decimal digits.
+    ///
+    /// Panics if `self` is zero.
+    pub fn ilog10(&self) -> u64 {
+        self.checked_ilog10()
+            .expect("argument of integer logarithm must be positive")
+    }
+
+    /// Returns the logarithm of `self` with respect to `base`, rounded down,
+    /// or `None` if `self` is zero or `base` is less than 2.
+    ///
+    /// This estimates the result from the top bits of both numbers, and then
+    /// corrects it exactly against the powers of `base`.
+    pub fn checked_ilog(&self, base: &Self) -> Option<u64> {
+        log::checked_ilog(self, base)
+    }
+
+    /// Returns the base 2 logarithm of `self`, rounded down, or `None` if `self`
+    /// is zero.
+    pub fn checked_ilog2(&self) -> Option<u64> {
+        self.bits().checked_sub(1)
+    }
+
+    /// Returns the base 10 logarithm of `self`, rounded down, or `None` if `self`
+    /// is zero.
+    pub fn checked_ilog10(&self) -> Option<u64> {
+        log::checked_ilog(self, &BigUint::from(10u8))
+    }
+
     /// Strips off trailing zero bigdigits - comparisons require the last element in the vector to
     /// be nonzero.
     #[inline]
@@ -867,6 +941,14 @@ impl BigUint {
         Pow::pow(self, exponent)
     }
 
+    /// Returns `self * self`.
+    ///
+    /// This is faster than a general multiplication, since each cross product
+    /// of the digits only needs to be computed once.
+    pub fn square(&self) -> Self {
+        multiplication::square3(&self.data)
+    }
+
     /// Returns `(self ^ exponent) % modulus`.
     ///
     /// Panics if the modulus is zero.
@@ -874,6 +956,221 @@ impl BigUint {
         power::modpow(self, exponent, modulus)
     }
 
+    /// Returns the product of `base ^ exponent` over all the `(base, exponent)`
+    /// pairs, modulo `modulus`.
+    ///
+    /// This is much faster than separate calls to [`modpow`](#method.modpow), as
+    /// the powers share their squarings, by Straus's method or, for many pairs,
+    /// Pippenger's method.
+    ///
+    /// Panics if the modulus is zero.
+    pub fn multi_modpow(pairs: &[(Self, Self)], modulus: &Self) -> Self {
+        multi_exp::multi_modpow(pairs, modulus)
+    }
+
+    /// Returns the modular multiplicative inverse of `self` modulo `modulus`,
+    /// that is `x` in `[0, modulus)` such that `self * x ≡ 1 (mod modulus)`,
+    /// or `None` if `self` and `modulus` are not coprime.
+    ///
+    /// Panics if the modulus is zero.
+    pub fn modinv(&self, modulus: &Self) -> Option<Self> {
+        gcd::modinv(self, modulus)
+    }
+
+    /// Returns `true` if `self` is probably prime.
+    ///
+    /// Values below 3317044064679887385961981 are tested exactly, by trial division
+    /// and Miller–Rabin with bases that are proven sufficient. Larger values are
+    /// tested with Baillie–PSW, for which no composite is known to pass, followed by
+    /// `rounds` further Miller–Rabin rounds with pseudo-random bases.
+    /// With `rounds == 0`, this is exactly the Baillie–PSW test.
+    pub fn is_probably_prime(&self, rounds: usize) -> bool {
+        prime::is_probably_prime(self, rounds)
+    }
+
+    /// Returns the smallest prime greater than `self`.
+    ///
+    /// This is exact for results below 3317044064679887385961981, and otherwise
+    /// a probable prime -- see [`is_probably_prime`](#method.is_probably_prime).
+    pub fn next_prime(&self) -> Self {
+        prime::next_prime(self)
+    }
+
+    /// Returns the largest prime less than `self`, or `None` if `self <= 2`.
+    ///
+    /// This is exact for values below 3317044064679887385961981, and otherwise
+    /// a probable prime -- see [`is_probably_prime`](#method.is_probably_prime).
+    pub fn prev_prime(&self) -> Option<Self> {
+        prime::prev_prime(self)
+    }
+
+    /// Solves the simultaneous congruences `x ≡ r (mod m)` for each pair `(r, m)`
+    /// by the Chinese remainder theorem, returning the least solution `x` and the
+    /// least common multiple of the moduli, or `None` if they are inconsistent.
+    ///
+    /// The moduli don't need to be coprime. To combine many sets of residues over
+    /// the same coprime moduli, a [`CrtBasis`](struct.CrtBasis.html) is faster.
+    ///
+    /// Panics if any modulus is zero.
+    pub fn crt(congruences: &[(Self, Self)]) -> Option<(Self, Self)> {
+        crt::crt(congruences)
+    }
+
+    /// Returns the factorial `n!`.
+    ///
+    /// This multiplies the prime factorization of the result by binary splitting,
+    /// which is much faster than multiplying the numbers up to `n` in turn.
+    pub fn factorial(n: u64) -> Self {
+        factorial::factorial(n)
+    }
+
+    /// Returns the double factorial `n!!`, the product of the numbers up to `n`
+    /// with the same parity as `n`.
+    pub fn double_factorial(n: u64) -> Self {
+        factorial::double_factorial(n)
+    }
+
+    /// Returns the binomial coefficient `n! / (k! (n - k)!)`, or zero if `k > n`.
+    pub fn binomial(n: u64, k: u64) -> Self {
+        factorial::binomial(n, k)
+    }
+
+    /// Returns the multinomial coefficient `(k_1 + ... + k_m)! / (k_1! ... k_m!)`.
+    ///
+    /// Panics if the sum of `ks` overflows a `u64`.
+    pub fn multinomial(ks: &[u64]) -> Self {
+        factorial::multinomial(ks)
+    }
+
+    /// Returns the primorial `n#`, the product of the primes up to `n`.
+    pub fn primorial(n: u64) -> Self {
+        factorial::primorial(n)
+    }
+
+    /// Returns the Fibonacci number `F(n)`, with `F(0) = 0` and `F(1) = 1`.
+    ///
+    /// This uses fast doubling, taking two squarings for each bit of `n`.
+    pub fn fibonacci(n: u64) -> Self {
+        lucas::fibonacci(n)
+    }
+
+    /// Returns the consecutive Fibonacci numbers `(F(n), F(n + 1))`.
+    pub fn fibonacci_pair(n: u64) -> (Self, Self) {
+        lucas::fibonacci_pair(n)
+    }
+
+    /// Returns the Lucas number `L(n)`, with `L(0) = 2` and `L(1) = 1`.
+    pub fn lucas(n: u64) -> Self {
+        lucas::lucas(n)
+    }
+
+    /// Returns `(U(n) % m, V(n) % m)` for the Lucas sequences with parameters `p`
+    /// and `q`, where `U(0) = 0`, `U(1) = 1`, `V(0) = 2`, `V(1) = p`, and both follow
+    /// `X(k + 2) = p X(k + 1) - q X(k)`.
+    ///
+    /// Negative parameters can be given by their residues modulo `m`, such as
+    /// `m - 1` for `q = -1`. The modulus doesn't have to be odd.
+    ///
+    /// Panics if the modulus is zero.
+    pub fn lucas_sequence(p: &Self, q: &Self, n: &Self, m: &Self) -> (Self, Self) {
+        lucas::lucas_sequence(p, q, n, m)
+    }
+
+    /// Returns the prime factorization of `self`, as far as the default limits of
+    /// a [`Factorizer`](struct.Factorizer.html) allow.
+    ///
+    /// Panics if `self` is zero.
+    pub fn factor(&self) -> Factorization {
+        Factorizer::new().factor(self)
+    }
+
+    /// Returns the least `x` with `base^x ≡ self (mod p)` for a prime `p`, or `None`
+    /// if there is none.
+    ///
+    /// This factors `p - 1` with [`factor`](#method.factor), and then uses
+    /// [`discrete_log_pohlig_hellman`](#method.discrete_log_pohlig_hellman).
+    /// Any cofactors it can't split are treated like primes, which works if
+    /// they're small enough for the generic methods. Only correct solutions are
+    /// returned in any case, and the result is unspecified if `p` isn't prime.
+    ///
+    /// Panics if `p` is zero.
+    pub fn discrete_log(&self, base: &Self, p: &Self) -> Option<Self> {
+        assert!(!p.is_zero(), "attempt to calculate with zero modulus!");
+        let order = (p - 1u32).max(BigUint::one()).factor();
+        let mut factors = order.factors().to_vec();
+        factors.extend_from_slice(order.cofactors());
+        discrete_log::pohlig_hellman(self, base, p, &factors)
+    }
+
+    /// Returns the least `x` with `base^x ≡ self (mod p)` for a prime `p`, or `None`
+    /// if there is none, by the Pohlig–Hellman algorithm.
+    ///
+    /// The prime factors and multiplicities in `order` must multiply to a multiple
+    /// of the order of `base`, such as `p - 1`. The logarithms in the subgroups of
+    /// prime order are found by baby-step giant-step, or Pollard's rho for those
+    /// above 32 bits.
+    ///
+    /// Panics if `p` is zero.
+    pub fn discrete_log_pohlig_hellman(
+        &self,
+        base: &Self,
+        p: &Self,
+        order: &[(Self, u32)],
+    ) -> Option<Self> {
+        assert!(!p.is_zero(), "attempt to calculate with zero modulus!");
+        discrete_log::pohlig_hellman(self, base, p, order)
+    }
+
+    /// Returns the least `x < bound` with `base^x ≡ self (mod modulus)`, or `None`
+    /// if there is none, by baby-step giant-step.
+    ///
+    /// This takes time and memory proportional to the square root of `bound`, and
+    /// `base` must be coprime to the modulus.
+    ///
+    /// Panics if the modulus is zero.
+    pub fn discrete_log_bsgs(&self, base: &Self, modulus: &Self, bound: &Self) -> Option<Self> {
+        assert!(
+            !modulus.is_zero(),
+            "attempt to calculate with zero modulus!"
+        );
+        discrete_log::bsgs(self, base, modulus, bound)
+    }
+
+    /// Returns `x < order` with `base^x ≡ self (mod p)` for a prime `p`, where `order`
+    /// is the order of `base`, or `None` if there is none, by Pollard's rho.
+    ///
+    /// This takes time proportional to the square root of `order`, which should
+    /// be prime, but little memory. It is randomized, and gives up with `None`
+    /// after a few unlucky attempts.
+    ///
+    /// Panics if `p` is zero.
+    pub fn discrete_log_rho(&self, base: &Self, p: &Self, order: &Self) -> Option<Self> {
+        assert!(!p.is_zero(), "attempt to calculate with zero modulus!");
+        discrete_log::rho(self, base, p, order)
+    }
+
+    /// Returns a square root of `self` modulo the prime `p`, or `None` if `self`
+    /// is not a quadratic residue modulo `p`.
+    ///
+    /// The result is the smaller of the two roots `x` and `p - x`. The primality
+    /// of `p` isn't checked, and the result is unspecified if it's not prime.
+    ///
+    /// Panics if `p` is zero.
+    pub fn sqrt_mod_prime(&self, p: &Self) -> Option<Self> {
+        sqrt_mod::sqrt_mod_prime(self, p)
+    }
+
+    /// Returns a square root of `self` modulo `p^k` for the prime `p`, or `None`
+    /// if there is none. The result is in the interval `[0, p^k)`.
+    ///
+    /// The primality of `p` isn't checked, and the result is unspecified if it's
+    /// not prime.
+    ///
+    /// Panics if `p` is zero.
+    pub fn sqrt_mod_prime_power(&self, p: &Self, k: u32) -> Option<Self> {
+        sqrt_mod::sqrt_mod_prime_power(self, p, k)
+    }
+
     /// Returns the truncated principal square root of `self` --
     /// see [Roots::sqrt](https://docs.rs/num-integer/0.1/num_integer/trait.Roots.html#method.sqrt)
     pub fn sqrt(&self) -> Self {
@@ -892,6 +1189,40 @@ impl BigUint {
         Roots::nth_root(self, n)
     }
 
+    /// Returns the truncated square root `s` of `self`, and the remainder
+    /// `self - s * s`.
+    pub fn sqrt_rem(&self) -> (Self, Self) {
+        let root = self.sqrt();
+        let rem = self - root.square();
+        (root, rem)
+    }
+
+    /// Returns the truncated `n`th root `r` of `self`, and the remainder
+    /// `self - r^n`.
+    ///
+    /// Panics if `n` is zero.
+    pub fn nth_root_rem(&self, n: u32) -> (Self, Self) {
+        let root = self.nth_root(n);
+        let rem = self - Pow::pow(&root, n);
+        (root, rem)
+    }
+
+    /// Returns `true` if `self` is the square of an integer.
+    ///
+    /// Most non-squares are rejected by their residues for a few small moduli,
+    /// without computing the square root.
+    pub fn is_perfect_square(&self) -> bool {
+        perfect_power::is_perfect_square(self)
+    }
+
+    /// Returns `Some((base, exp))` with the largest `exp >= 2` such that
+    /// `self == base^exp`, or `None` if `self` is not a perfect power.
+    ///
+    /// This is `None` for 0 and 1, which have no largest exponent.
+    pub fn is_perfect_power(&self) -> Option<(Self, u32)> {
+        perfect_power::perfect_power(self)
+    }
+
     /// Returns the number of least-significant bits that are zero,
     /// or `None` if the entire number is zero.
     pub fn trailing_zeros(&self) -> Option<u64> {
@@ -0,0 +1,102 @@
+use super::division::BURNIKEL_ZIEGLER_THRESHOLD;
+use super::multiplication::{mul_high, mul_low};
+use super::power::sliding_window_pow;
+use super::{biguint_from_vec, BigUint};
+
+use crate::big_digit;
+
+use core::cmp;
+
+use num_traits::{One, Zero};
+
+/// Precomputed data for Barrett reduction modulo a fixed modulus, to make
+/// many reductions cheaper than a division each.
+///
+/// Unlike [`MontgomeryContext`], this works with any non-zero modulus,
+/// including even ones, and with values in their usual form.
+///
+/// [`MontgomeryContext`]: struct.MontgomeryContext.html
+#[derive(Clone, Debug)]
+pub struct BarrettReducer {
+    modulus: BigUint,
+    /// floor(2**(2*_W*k) / m), or zero for moduli that are reduced by division.
+    mu: BigUint,
+}
+
+impl BarrettReducer {
+    /// Creates a reducer for the given modulus.
+    ///
+    /// Panics if the modulus is zero.
+    pub fn new(modulus: &BigUint) -> Self {
+        assert!(
+            !modulus.is_zero(),
+            "attempt to calculate with zero modulus!"
+        );
+        let len = modulus.data.len();
+        let mu = if len < BURNIKEL_ZIEGLER_THRESHOLD {
+            (BigUint::one() << (2 * len as u64 * u64::from(big_digit::BITS))) / modulus
+        } else {
+            BigUint::zero()
+        };
+        BarrettReducer {
+            modulus: modulus.clone(),
+            mu,
+        }
+    }
+
+    /// Returns the modulus of this reducer.
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    /// Returns `x % m`.
+    ///
+    /// This is fastest for `x` less than the square of `2**(_W*k)`, where `_W`
+    /// is the digit size and `k` the number of digits of the modulus, such as
+    /// the product of two reduced values. Larger `x` just fall back to a
+    /// division, as do all `x` for large moduli, where the division by
+    /// Burnikel and Ziegler's algorithm is at least as fast.
+    pub fn reduce(&self, x: &BigUint) -> BigUint {
+        let k = self.modulus.data.len();
+        if x.data.len() > 2 * k || k >= BURNIKEL_ZIEGLER_THRESHOLD {
+            return x % &self.modulus;
+        }
+        if x.data.len() < k {
+            return x.clone();
+        }
+
+        // The estimate q = floor(floor(x / b^(k-1)) mu / b^(k+1)) of x / m is
+        // never too large, and at most 2 too small, or 3 with the low digits
+        // of the product left out. The remainder x - q m is then less than
+        // 4 m < b^(k+1), so it only needs the low digits too.
+        let q = mul_high(&x.data[k - 1..], &self.mu.data, k + 1);
+        let qm = mul_low(&q.data, &self.modulus.data, k + 1);
+        let mut r = biguint_from_vec(x.data[..cmp::min(x.data.len(), k + 1)].to_vec());
+        if r < qm {
+            r.set_bit((k as u64 + 1) * u64::from(big_digit::BITS), true);
+        }
+        r -= qm;
+        while r >= self.modulus {
+            r -= &self.modulus;
+        }
+        r
+    }
+
+    /// Returns `x * y % m`.
+    pub fn mul_mod(&self, x: &BigUint, y: &BigUint) -> BigUint {
+        self.reduce(&(x * y))
+    }
+
+    /// Returns `base ** exponent % m`.
+    pub fn pow_mod(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        if exponent.is_zero() {
+            return BigUint::one() % &self.modulus;
+        }
+        sliding_window_pow(
+            self.reduce(base),
+            &exponent.data,
+            |x, y| self.mul_mod(x, y),
+            |x| self.reduce(&x.square()),
+        )
+    }
+}
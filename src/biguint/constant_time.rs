@@ -0,0 +1,488 @@
+use super::monty::inv_mod_alt;
+use super::secret::{wipe, SecretDigits};
+use super::{biguint_from_vec, BigUint};
+
+use crate::big_digit::{self, BigDigit, DoubleBigDigit};
+use crate::std_alloc::Vec;
+
+use core::cmp;
+use core::fmt;
+
+use num_integer::Integer;
+
+// The helpers below work on slices of equal, public length, and avoid any
+// branches or memory accesses that depend on the digit values. Conditions are
+// passed around as masks of all ones or all zeros.
+
+/// Returns a mask of all ones if `bit` is 1, or all zeros if it's 0.
+#[inline]
+fn mask(bit: BigDigit) -> BigDigit {
+    (0 as BigDigit).wrapping_sub(bit)
+}
+
+/// Returns 1 if `x` is zero, otherwise 0.
+#[inline]
+fn is_zero_bit(x: BigDigit) -> BigDigit {
+    ((x | x.wrapping_neg()) >> (big_digit::BITS - 1)) ^ 1
+}
+
+/// Sets `z` to `x` where the mask is set.
+fn cmov(z: &mut [BigDigit], x: &[BigDigit], m: BigDigit) {
+    for (zi, &xi) in z.iter_mut().zip(x) {
+        *zi ^= (*zi ^ xi) & m;
+    }
+}
+
+/// Swaps `x` and `y` where the mask is set.
+fn cswap(x: &mut [BigDigit], y: &mut [BigDigit], m: BigDigit) {
+    for (xi, yi) in x.iter_mut().zip(y) {
+        let t = (*xi ^ *yi) & m;
+        *xi ^= t;
+        *yi ^= t;
+    }
+}
+
+/// Sets `z = x + y`, returning the carry.
+fn add(z: &mut [BigDigit], x: &[BigDigit], y: &[BigDigit]) -> BigDigit {
+    let mut carry = 0;
+    for (zi, (&xi, &yi)) in z.iter_mut().zip(x.iter().zip(y)) {
+        let t = DoubleBigDigit::from(xi) + DoubleBigDigit::from(yi) + carry;
+        *zi = t as BigDigit;
+        carry = t >> big_digit::BITS;
+    }
+    carry as BigDigit
+}
+
+/// Sets `z = x - y`, returning the borrow.
+fn sub(z: &mut [BigDigit], x: &[BigDigit], y: &[BigDigit]) -> BigDigit {
+    let mut borrow = 0;
+    for (zi, (&xi, &yi)) in z.iter_mut().zip(x.iter().zip(y)) {
+        let t = DoubleBigDigit::from(xi)
+            .wrapping_sub(DoubleBigDigit::from(yi))
+            .wrapping_sub(borrow);
+        *zi = t as BigDigit;
+        borrow = (t >> big_digit::BITS) & 1;
+    }
+    borrow as BigDigit
+}
+
+/// Shifts `z` right by one bit, shifting `top` in at the top.
+fn shr1(z: &mut [BigDigit], top: BigDigit) {
+    let mut carry = top;
+    for zi in z.iter_mut().rev() {
+        let low = *zi & 1;
+        *zi = (*zi >> 1) | (carry << (big_digit::BITS - 1));
+        carry = low;
+    }
+}
+
+/// An unsigned integer with a fixed width, for use as a secret value with
+/// [`ConstantTimeContext`].
+///
+/// Unlike `BigUint`, this keeps any leading zeros, so its length only reveals
+/// the width and not the magnitude of the value. It also doesn't implement
+/// `PartialEq` or print its value with `Debug`; compare with
+/// [`ct_eq`](#method.ct_eq) instead.
+///
+/// The digits are wiped when the value is dropped, and so are all the
+/// temporaries of the arithmetic in `ConstantTimeContext`. Values converted
+/// to other types, such as with [`to_biguint`](#method.to_biguint) or
+/// [`to_bytes_be`](#method.to_bytes_be), aren't covered by this, and neither
+/// is any arithmetic on `BigUint`.
+///
+/// [`ConstantTimeContext`]: struct.ConstantTimeContext.html
+#[derive(Clone)]
+pub struct FixedUint {
+    data: SecretDigits,
+    bits: u64,
+}
+
+impl fmt::Debug for FixedUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FixedUint")
+            .field("bits", &self.bits)
+            .finish()
+    }
+}
+
+impl FixedUint {
+    /// Returns the number of digits of a value `bits` wide.
+    fn digits(bits: u64) -> usize {
+        Integer::div_ceil(&bits, &u64::from(big_digit::BITS)) as usize
+    }
+
+    /// Creates a value of the given width in bits from `x`.
+    ///
+    /// Note that the length of `x` itself isn't hidden, so secrets are better
+    /// created with [`from_bytes_be`](#method.from_bytes_be) or
+    /// [`from_bytes_le`](#method.from_bytes_le).
+    ///
+    /// Panics if `x` doesn't fit in `bits` bits.
+    pub fn from_biguint(x: &BigUint, bits: u64) -> Self {
+        assert!(x.bits() <= bits, "value doesn't fit in the fixed width");
+        FixedUint {
+            data: SecretDigits::padded(&x.data, FixedUint::digits(bits)),
+            bits,
+        }
+    }
+
+    /// Creates a value from big-endian bytes, with a width of all the bytes.
+    pub fn from_bytes_be(bytes: &[u8]) -> Self {
+        FixedUint::from_bytes(bytes.iter().rev(), bytes.len())
+    }
+
+    /// Creates a value from little-endian bytes, with a width of all the bytes.
+    pub fn from_bytes_le(bytes: &[u8]) -> Self {
+        FixedUint::from_bytes(bytes.iter(), bytes.len())
+    }
+
+    /// Creates a value from `len` bytes in little-endian order.
+    fn from_bytes<'a, I>(bytes: I, len: usize) -> Self
+    where
+        I: Iterator<Item = &'a u8>,
+    {
+        let bits = len as u64 * 8;
+        let digit_bytes = usize::from(big_digit::BITS / 8);
+        let mut data = SecretDigits::zeroed(FixedUint::digits(bits));
+        for (i, &b) in bytes.enumerate() {
+            data[i / digit_bytes] |= BigDigit::from(b) << (i % digit_bytes * 8);
+        }
+        FixedUint { data, bits }
+    }
+
+    /// Returns the value as a `BigUint`, which no longer hides its magnitude,
+    /// and isn't wiped when dropped.
+    pub fn to_biguint(&self) -> BigUint {
+        biguint_from_vec(self.data.to_vec())
+    }
+
+    /// Returns the value as big-endian bytes, enough for the whole width.
+    ///
+    /// The bytes aren't wiped when dropped, which is left to the caller.
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let mut bytes = self.to_bytes_le();
+        bytes.reverse();
+        bytes
+    }
+
+    /// Returns the value as little-endian bytes, enough for the whole width.
+    ///
+    /// The bytes aren't wiped when dropped, which is left to the caller.
+    pub fn to_bytes_le(&self) -> Vec<u8> {
+        let len = Integer::div_ceil(&self.bits, &8) as usize;
+        let digit_bytes = usize::from(big_digit::BITS / 8);
+        (0..len)
+            .map(|i| (self.data[i / digit_bytes] >> (i % digit_bytes * 8)) as u8)
+            .collect()
+    }
+
+    /// Returns the width of this value in bits.
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    /// Returns `true` if `self` and `other` are equal, in time that only
+    /// depends on their widths.
+    pub fn ct_eq(&self, other: &Self) -> bool {
+        let len = cmp::max(self.data.len(), other.data.len());
+        let digit = |x: &Self, i: usize| x.data.get(i).cloned().unwrap_or(0);
+        let diff = (0..len).fold(0, |acc, i| acc | (digit(self, i) ^ digit(other, i)));
+        is_zero_bit(diff) == 1
+    }
+
+    /// Returns `b` if `choice` is `true`, or `a` otherwise, in time that only
+    /// depends on their widths.
+    ///
+    /// Panics if `a` and `b` have different widths.
+    pub fn ct_select(a: &Self, b: &Self, choice: bool) -> Self {
+        assert_eq!(a.bits, b.bits, "values have different widths");
+        let mut z = a.clone();
+        cmov(&mut z.data, &b.data, mask(BigDigit::from(choice)));
+        z
+    }
+}
+
+/// Precomputed data for constant-time modular arithmetic with a fixed odd
+/// modulus, for working with secret values.
+///
+/// All the operations take time that depends only on the widths of the
+/// modulus and the [`FixedUint`] arguments, and not on their values: they use
+/// Montgomery multiplication with fixed loops, and select values with masks
+/// instead of branches or secret table indexes. So the modulus may be secret
+/// too, like a prime factor of an RSA key, and the context wipes its copies
+/// of it when dropped. The `BigUint` that it's created from, or returned by
+/// [`modulus`](#method.modulus) after a clone, is left to the caller.
+///
+/// This is separate from the variable-time arithmetic on `BigUint`, such as
+/// `modpow` and [`MontgomeryContext`], which is much faster for public values.
+///
+/// [`FixedUint`]: struct.FixedUint.html
+/// [`MontgomeryContext`]: struct.MontgomeryContext.html
+#[derive(Clone)]
+pub struct ConstantTimeContext {
+    modulus: BigUint,
+    /// The digits of the modulus, which all the residues have as many of.
+    m: SecretDigits,
+    /// -m**-1 mod 2**BITS
+    n0inv: BigDigit,
+    /// 2**(2*_W*len(m)) mod m
+    rr: SecretDigits,
+    /// 1, which also converts residues out of Montgomery form.
+    one: SecretDigits,
+}
+
+impl fmt::Debug for ConstantTimeContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConstantTimeContext")
+            .field("bits", &self.modulus.bits())
+            .finish()
+    }
+}
+
+impl Drop for ConstantTimeContext {
+    fn drop(&mut self) {
+        // The other fields wipe themselves.
+        wipe(&mut self.modulus.data);
+    }
+}
+
+/// The window width of the constant-time exponentiation, which divides the
+/// digit size.
+const WINDOW_BITS: u8 = 4;
+
+impl ConstantTimeContext {
+    /// Creates a context for the given modulus.
+    ///
+    /// Panics if the modulus is even, including zero.
+    pub fn new(modulus: &BigUint) -> Self {
+        assert!(
+            modulus.is_odd(),
+            "constant-time arithmetic requires an odd modulus!"
+        );
+        let n = modulus.data.len();
+        let mut one = SecretDigits::zeroed(n);
+        one[0] = 1;
+        let mut ctx = ConstantTimeContext {
+            modulus: modulus.clone(),
+            m: SecretDigits::padded(&modulus.data, n),
+            n0inv: inv_mod_alt(modulus.data[0]),
+            rr: SecretDigits::zeroed(n),
+            one,
+        };
+
+        // Double 1 mod m up to 2**(2*_W*n) mod m. Unlike a division, this takes
+        // time independent of the modulus, and leaves no temporaries behind.
+        let mut rr = ctx.add_mod(&ctx.rr, &ctx.one);
+        for _ in 0..2 * n * big_digit::BITS as usize {
+            rr = ctx.add_mod(&rr, &rr);
+        }
+        ctx.rr = rr;
+        ctx
+    }
+
+    /// Returns the modulus of this context.
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    /// Returns `x * y * 2**(-_W*len(m)) mod m`, for `x < 2**(_W*len(m))` and
+    /// `y < m`, by coarsely integrated operand scanning.
+    fn montgomery(&self, x: &[BigDigit], y: &[BigDigit]) -> SecretDigits {
+        let n = self.m.len();
+        let w = big_digit::BITS;
+        let wide = DoubleBigDigit::from;
+
+        let mut t = SecretDigits::zeroed(n + 2);
+        for &yi in y {
+            // t += x * y[i]
+            let mut c = 0;
+            for j in 0..n {
+                let z = wide(x[j]) * wide(yi) + wide(t[j]) + c;
+                t[j] = z as BigDigit;
+                c = z >> w;
+            }
+            let z = wide(t[n]) + c;
+            t[n] = z as BigDigit;
+            t[n + 1] = (z >> w) as BigDigit;
+
+            // t = (t + u * m) / 2**_W, where u makes the division exact
+            let u = t[0].wrapping_mul(self.n0inv);
+            let mut c = (wide(t[0]) + wide(u) * wide(self.m[0])) >> w;
+            for j in 1..n {
+                let z = wide(t[j]) + wide(u) * wide(self.m[j]) + c;
+                t[j - 1] = z as BigDigit;
+                c = z >> w;
+            }
+            let z = wide(t[n]) + c;
+            t[n - 1] = z as BigDigit;
+            t[n] = t[n + 1] + (z >> w) as BigDigit;
+        }
+
+        // The result is less than 2m, so subtract m unless that borrows.
+        let mut z = SecretDigits::zeroed(n);
+        let borrow = sub(&mut z, &t[..n], &self.m);
+        let keep = mask(borrow & (t[n] ^ 1));
+        cmov(&mut z, &t[..n], keep);
+        z
+    }
+
+    /// Returns `x + y mod m`, for `x` and `y` less than `m`.
+    fn add_mod(&self, x: &[BigDigit], y: &[BigDigit]) -> SecretDigits {
+        let n = self.m.len();
+        let mut t = SecretDigits::zeroed(n);
+        let carry = add(&mut t, x, y);
+        let mut z = SecretDigits::zeroed(n);
+        let borrow = sub(&mut z, &t, &self.m);
+        cmov(&mut z, &t, mask(borrow & (carry ^ 1)));
+        z
+    }
+
+    /// Returns `x - y mod m`, for `x` and `y` less than `m`.
+    fn sub_mod(&self, x: &[BigDigit], y: &[BigDigit]) -> SecretDigits {
+        let n = self.m.len();
+        let mut z = SecretDigits::zeroed(n);
+        let borrow = sub(&mut z, x, y);
+        let mut t = SecretDigits::zeroed(n);
+        add(&mut t, &z, &self.m);
+        cmov(&mut z, &t, mask(borrow));
+        z
+    }
+
+    /// Returns `x mod m` in Montgomery form, by Horner's rule on chunks of
+    /// `x` as long as the modulus, from the top, after padding `x` to a whole
+    /// number of chunks.
+    fn to_monty(&self, x: &FixedUint) -> SecretDigits {
+        let n = self.m.len();
+        let mut acc = SecretDigits::zeroed(n);
+        let len = Integer::div_ceil(&x.data.len(), &n) * n;
+        let x = SecretDigits::padded(&x.data, len);
+        for chunk in x.rchunks_exact(n) {
+            let shifted = self.montgomery(&acc, &self.rr);
+            let reduced = self.montgomery(chunk, &self.one);
+            acc = self.add_mod(&shifted, &self.montgomery(&reduced, &self.rr));
+        }
+        self.montgomery(&acc, &self.rr)
+    }
+
+    /// Converts `x` out of Montgomery form.
+    fn to_fixed(&self, x: &[BigDigit]) -> FixedUint {
+        FixedUint {
+            data: self.montgomery(x, &self.one),
+            bits: self.modulus.bits(),
+        }
+    }
+
+    /// Returns `x * y mod m`.
+    pub fn mul(&self, x: &FixedUint, y: &FixedUint) -> FixedUint {
+        let z = self.montgomery(&self.to_monty(x), &self.to_monty(y));
+        self.to_fixed(&z)
+    }
+
+    /// Returns `base ** exponent mod m`.
+    ///
+    /// This goes through every bit of the exponent's width, four at a time,
+    /// and always multiplies by a power of the base, which is looked up by
+    /// reading the whole table of powers.
+    pub fn modpow(&self, base: &FixedUint, exponent: &FixedUint) -> FixedUint {
+        let w = WINDOW_BITS;
+        let size = 1 << w;
+        let digit_windows = big_digit::BITS / w;
+
+        // table[i] contains base^i
+        let x = self.to_monty(base);
+        let mut table = Vec::with_capacity(size);
+        table.push(self.montgomery(&self.one, &self.rr));
+        table.push(x.clone());
+        for i in 2..size {
+            let next = self.montgomery(&table[i - 1], &x);
+            table.push(next);
+        }
+
+        let mut acc = table[0].clone();
+        let mut power = SecretDigits::zeroed(self.m.len());
+        for &d in exponent.data.iter().rev() {
+            for k in (0..digit_windows).rev() {
+                for _ in 0..w {
+                    acc = self.montgomery(&acc, &acc);
+                }
+                let window = (d >> (k * w)) as usize & (size - 1);
+                for (i, entry) in table.iter().enumerate() {
+                    let eq = is_zero_bit((i ^ window) as BigDigit);
+                    cmov(&mut power, entry, mask(eq));
+                }
+                acc = self.montgomery(&acc, &power);
+            }
+        }
+        self.to_fixed(&acc)
+    }
+
+    /// Returns the modular multiplicative inverse of `x`, or `None` if `x` and
+    /// the modulus are not coprime.
+    ///
+    /// This is the binary extended Euclidean algorithm, with a fixed number of
+    /// iterations and all the steps taken under masks.
+    pub fn modinv(&self, x: &FixedUint) -> Option<FixedUint> {
+        let n = self.m.len();
+        let mut a = self.montgomery(&self.to_monty(x), &self.one);
+        let mut b = SecretDigits::padded(&self.m, n);
+        let mut u = SecretDigits::padded(&self.one, n);
+        let mut v = SecretDigits::zeroed(n);
+
+        // Invariants: a = u x and b = v x (mod m), with b odd. Each iteration
+        // at least halves a b < m^2, so a ends up zero and b the gcd.
+        let mut diff = SecretDigits::zeroed(n);
+        let mut m_masked = SecretDigits::zeroed(n);
+        for _ in 0..2 * self.modulus.bits() {
+            // If a is odd, make sure a >= b, then subtract.
+            let odd = mask(a[0] & 1);
+            let less = mask(sub(&mut diff, &a, &b));
+            cswap(&mut a, &mut b, odd & less);
+            cswap(&mut u, &mut v, odd & less);
+            sub(&mut diff, &a, &b);
+            cmov(&mut a, &diff, odd);
+            let u_diff = self.sub_mod(&u, &v);
+            cmov(&mut u, &u_diff, odd);
+
+            // Halve a, which is now even, and u modulo m.
+            shr1(&mut a, 0);
+            let u_odd = mask(u[0] & 1);
+            for (mi, &d) in m_masked.iter_mut().zip(self.m.iter()) {
+                *mi = d & u_odd;
+            }
+            let u_copy = u.clone();
+            let carry = add(&mut u, &u_copy, &m_masked);
+            shr1(&mut u, carry);
+        }
+
+        let gcd_is_one = b
+            .iter()
+            .zip(self.one.iter())
+            .fold(0, |acc, (&bi, &oi)| acc | (bi ^ oi));
+        if is_zero_bit(gcd_is_one) == 1 {
+            Some(FixedUint {
+                data: v,
+                bits: self.modulus.bits(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn test_constant_time_rr() {
+    use num_traits::One;
+
+    let big = (BigUint::one() << 300u16) - 153u32;
+    for m in &[
+        BigUint::one(),
+        BigUint::from(3u32),
+        BigUint::from(!0u32),
+        big,
+    ] {
+        let ctx = ConstantTimeContext::new(m);
+        let n = m.data.len() as u64;
+        let rr = (BigUint::one() << (2 * n * u64::from(big_digit::BITS))) % m;
+        assert_eq!(biguint_from_vec(ctx.rr.to_vec()), rr);
+    }
+}
@@ -15,7 +15,7 @@ use core::cmp::Ordering::{Equal, Greater, Less};
 use core::convert::TryFrom;
 use core::mem;
 use core::str::FromStr;
-use num_integer::{Integer, Roots};
+use num_integer::Integer;
 use num_traits::float::FloatCore;
 use num_traits::{FromPrimitive, Num, PrimInt, ToPrimitive, Zero};
 
@@ -97,11 +97,70 @@ fn from_inexact_bitwise_digits_le(v: &[u8], bits: u8) -> BigUint {
     biguint_from_vec(data)
 }
 
-// Read little-endian radix digits
+/// The number of radix digits above which `from_radix_digits_be` splits the input
+/// in a divide-and-conquer fashion, instead of accumulating one digit at a time.
+const FROM_RADIX_DIVIDE_THRESHOLD: usize = 2000;
+
+/// The number of big digits above which `to_radix_digits_le` splits the number
+/// in a divide-and-conquer fashion, instead of dividing out one digit at a time.
+const TO_RADIX_DIVIDE_THRESHOLD: usize = 8;
+
+/// Returns the powers `base^(2^k)`, each the square of the last, stopping after the
+/// first one whose square has more than `len` big digits.
+///
+/// This isn't cached between conversions: the squarings cost only a few percent
+/// of the divisions that use them, and a cache would need `std` and keep memory
+/// as large as the largest number converted.
+fn radix_power_tree(base: BigDigit, len: usize) -> Vec<BigUint> {
+    let mut powers = vec![BigUint::from(base)];
+    loop {
+        let last = powers.last().unwrap();
+        if last.data.len() * 2 - 1 > len {
+            return powers;
+        }
+        let next = last.square();
+        powers.push(next);
+    }
+}
+
+// Read big-endian radix digits
 fn from_radix_digits_be(v: &[u8], radix: u32) -> BigUint {
     debug_assert!(!v.is_empty() && !radix.is_power_of_two());
     debug_assert!(v.iter().all(|&c| u32::from(c) < radix));
 
+    if v.len() <= FROM_RADIX_DIVIDE_THRESHOLD {
+        return from_radix_digits_be_small(v, radix);
+    }
+
+    // Split the digits into halves of `power * 2^k` digits, which convert separately
+    // and combine as `hi * base^(2^k) + lo`, with a shared tree of those powers.
+    let (base, power) = get_radix_base(radix, big_digit::BITS);
+    let mut powers = vec![BigUint::from(base)];
+    while power << powers.len() < v.len() {
+        let next = powers.last().unwrap().square();
+        powers.push(next);
+    }
+    from_radix_digits_be_rec(v, radix, power, &powers)
+}
+
+fn from_radix_digits_be_rec(v: &[u8], radix: u32, power: usize, powers: &[BigUint]) -> BigUint {
+    if v.len() <= FROM_RADIX_DIVIDE_THRESHOLD {
+        return from_radix_digits_be_small(v, radix);
+    }
+
+    // The largest power with fewer digits than the input.
+    let mut k = powers.len() - 1;
+    while power << k >= v.len() {
+        k -= 1;
+    }
+
+    let (hi, lo) = v.split_at(v.len() - (power << k));
+    let hi = from_radix_digits_be_rec(hi, radix, power, powers);
+    let lo = from_radix_digits_be_rec(lo, radix, power, powers);
+    hi * &powers[k] + lo
+}
+
+fn from_radix_digits_be_small(v: &[u8], radix: u32) -> BigUint {
     #[cfg(feature = "std")]
     let radix_log2 = f64::from(radix).log2();
     #[cfg(not(feature = "std"))]
@@ -655,54 +714,32 @@ pub(super) fn to_radix_digits_le(u: &BigUint, radix: u32) -> Vec<u8> {
     let radix_digits = ((u.bits() as f64) / radix_log2).ceil();
     let mut res = Vec::with_capacity(radix_digits.to_usize().unwrap_or(0));
 
-    let mut digits = u.clone();
-
     let (base, power) = get_radix_base(radix, big_digit::HALF_BITS);
     let radix = radix as BigDigit;
 
-    // For very large numbers, the O(n²) loop of repeated `div_rem_digit` dominates the
-    // performance. We can mitigate this by dividing into chunks of a larger base first.
-    // The threshold for this was chosen by anecdotal performance measurements to
-    // approximate where this starts to make a noticeable difference.
-    if digits.data.len() >= 64 {
-        let mut big_base = BigUint::from(base * base);
-        let mut big_power = 2usize;
-
-        // Choose a target base length near √n.
-        let target_len = digits.data.len().sqrt();
-        while big_base.data.len() < target_len {
-            big_base = &big_base * &big_base;
-            big_power *= 2;
-        }
-
-        // This outer loop will run approximately √n times.
-        while digits > big_base {
-            // This is still the dominating factor, with n digits divided by √n digits.
-            let (q, mut big_r) = digits.div_rem(&big_base);
-            digits = q;
-
-            // This inner loop now has O(√n²)=O(n) behavior altogether.
-            for _ in 0..big_power {
-                let (q, mut r) = div_rem_digit(big_r, base);
-                big_r = q;
-                for _ in 0..power {
-                    res.push((r % radix) as u8);
-                    r /= radix;
-                }
-            }
-        }
+    // First split the number into chunks of `power` digits, each less than `base`.
+    let mut chunks = Vec::with_capacity(res.capacity() / power + 1);
+    if u.data.len() < TO_RADIX_DIVIDE_THRESHOLD {
+        to_radix_chunks_le(&mut chunks, u.clone(), base, None);
+    } else {
+        // For very large numbers, the O(n²) loop of repeated `div_rem_digit` dominates the
+        // performance. Instead, we recursively split the number in halves by dividing by
+        // `base^(2^k)`, with a tree of those powers computed just once.
+        let powers = radix_power_tree(base, u.data.len());
+        let k = powers.len() - 1;
+        to_radix_chunks_le_rec(&mut chunks, u.clone(), &powers, k, false);
     }
 
-    while digits.data.len() > 1 {
-        let (q, mut r) = div_rem_digit(digits, base);
+    let (&last, chunks) = chunks.split_last().unwrap();
+    for &chunk in chunks {
+        let mut r = chunk;
         for _ in 0..power {
             res.push((r % radix) as u8);
             r /= radix;
         }
-        digits = q;
     }
 
-    let mut r = digits.data[0];
+    let mut r = last;
     while r != 0 {
         res.push((r % radix) as u8);
         r /= radix;
@@ -711,6 +748,53 @@ pub(super) fn to_radix_digits_le(u: &BigUint, radix: u32) -> Vec<u8> {
     res
 }
 
+/// Pushes the chunks of `u < base^(2^(k+1))`, padded with zeros to exactly
+/// `2^(k+1)` chunks if `pad` is set.
+fn to_radix_chunks_le_rec(
+    chunks: &mut Vec<BigDigit>,
+    u: BigUint,
+    powers: &[BigUint],
+    k: usize,
+    pad: bool,
+) {
+    if u.data.len() < TO_RADIX_DIVIDE_THRESHOLD {
+        let len = if pad { Some(2 << k) } else { None };
+        to_radix_chunks_le(chunks, u, powers[0].data[0], len);
+        return;
+    }
+
+    // Since u has at least two big digits, it's larger than base^2, so k > 0.
+    let (hi, lo) = u.div_rem(&powers[k]);
+    if hi.is_zero() && !pad {
+        // Don't pad the most significant part with leading zeros.
+        to_radix_chunks_le_rec(chunks, lo, powers, k - 1, false);
+    } else {
+        to_radix_chunks_le_rec(chunks, lo, powers, k - 1, true);
+        to_radix_chunks_le_rec(chunks, hi, powers, k - 1, pad);
+    }
+}
+
+/// Pushes the chunks of `u`, dividing by `base` one at a time, padded with
+/// zeros to `len` chunks if given.
+fn to_radix_chunks_le(
+    chunks: &mut Vec<BigDigit>,
+    mut u: BigUint,
+    base: BigDigit,
+    len: Option<usize>,
+) {
+    let start = chunks.len();
+    while !u.is_zero() {
+        let (q, r) = div_rem_digit(u, base);
+        chunks.push(r);
+        u = q;
+    }
+
+    if let Some(len) = len {
+        debug_assert!(chunks.len() - start <= len);
+        chunks.resize(start + len, 0);
+    }
+}
+
 pub(super) fn to_radix_le(u: &BigUint, radix: u32) -> Vec<u8> {
     if u.is_zero() {
         vec![0]
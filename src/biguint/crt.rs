@@ -0,0 +1,134 @@
+use super::gcd::modinv;
+use super::BigUint;
+
+use crate::std_alloc::Vec;
+
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+/// Returns `(x - y) mod m`, for `x` already reduced modulo `m`.
+fn sub_mod(x: BigUint, y: &BigUint, m: &BigUint) -> BigUint {
+    let y = y % m;
+    if x >= y {
+        x - y
+    } else {
+        x + m - y
+    }
+}
+
+/// Solves the congruences `x = r (mod m)` for each pair `(r, m)`, returning
+/// the least solution and the least common multiple of the moduli, or `None`
+/// if the congruences are inconsistent.
+pub(super) fn crt(congruences: &[(BigUint, BigUint)]) -> Option<(BigUint, BigUint)> {
+    let mut x = BigUint::zero();
+    let mut lcm = BigUint::one();
+    for (r, m) in congruences {
+        assert!(!m.is_zero(), "attempt to calculate with zero modulus!");
+
+        // With g = gcd(lcm, m), a solution exists iff x = r (mod g), and then
+        // x + lcm * t solves both for t = (r - x) / g * (lcm / g)^-1 mod m / g.
+        let g = lcm.gcd(m);
+        let diff = sub_mod(r % m, &x, m);
+        let (diff, rem) = diff.div_rem(&g);
+        if !rem.is_zero() {
+            return None;
+        }
+        let m_g = m / &g;
+        let inv = modinv(&(&lcm / &g), &m_g).unwrap();
+        let t = diff * inv % &m_g;
+        x += &lcm * t;
+        lcm *= m_g;
+    }
+    Some((x, lcm))
+}
+
+/// A precomputed basis of pairwise coprime moduli, for recombining residues by
+/// the Chinese remainder theorem with Garner's algorithm.
+///
+/// This is faster than [`BigUint::crt`](struct.BigUint.html#method.crt) when
+/// many sets of residues are combined over the same moduli.
+#[derive(Clone, Debug)]
+pub struct CrtBasis {
+    moduli: Vec<BigUint>,
+    /// The products of the moduli before each one.
+    prefixes: Vec<BigUint>,
+    /// Each prefix product inverted modulo the following modulus.
+    inverses: Vec<BigUint>,
+    product: BigUint,
+}
+
+impl CrtBasis {
+    /// Creates a basis for the given moduli, or returns `None` if they are not
+    /// pairwise coprime.
+    ///
+    /// Panics if any modulus is zero.
+    pub fn new(moduli: &[BigUint]) -> Option<Self> {
+        let mut prefixes = Vec::with_capacity(moduli.len());
+        let mut inverses = Vec::with_capacity(moduli.len());
+        let mut product = BigUint::one();
+        for m in moduli {
+            assert!(!m.is_zero(), "attempt to calculate with zero modulus!");
+            inverses.push(modinv(&product, m)?);
+            let next = &product * m;
+            prefixes.push(product);
+            product = next;
+        }
+        Some(CrtBasis {
+            moduli: moduli.to_vec(),
+            prefixes,
+            inverses,
+            product,
+        })
+    }
+
+    /// Returns the moduli of this basis.
+    pub fn moduli(&self) -> &[BigUint] {
+        &self.moduli
+    }
+
+    /// Returns the product of the moduli of this basis.
+    pub fn product(&self) -> &BigUint {
+        &self.product
+    }
+
+    /// Returns the unique `x` in `[0, product)` with `x = residues[i]` modulo
+    /// each `moduli[i]`.
+    ///
+    /// Panics if the number of residues differs from the number of moduli.
+    pub fn combine(&self, residues: &[BigUint]) -> BigUint {
+        assert_eq!(
+            residues.len(),
+            self.moduli.len(),
+            "expected one residue per modulus"
+        );
+
+        // Garner's algorithm builds x in mixed radix, with each step making
+        // x correct modulo one more modulus.
+        let mut x = BigUint::zero();
+        let iter = self.moduli.iter().zip(&self.prefixes).zip(&self.inverses);
+        for (((m, prefix), inv), r) in iter.zip(residues) {
+            let t = sub_mod(r % m, &x, m) * inv % m;
+            x += prefix * t;
+        }
+        x
+    }
+}
+
+#[test]
+fn test_crt_basis() {
+    let moduli: Vec<BigUint> = [3u32, 5, 7, 11, 13, 16]
+        .iter()
+        .map(|&m| BigUint::from(m))
+        .collect();
+    let basis = CrtBasis::new(&moduli).unwrap();
+    assert_eq!(*basis.product(), BigUint::from(240240u32));
+
+    for &x in &[0u32, 1, 2, 12345, 240239] {
+        let x = BigUint::from(x);
+        let residues: Vec<BigUint> = moduli.iter().map(|m| &x % m).collect();
+        assert_eq!(basis.combine(&residues), x);
+
+        let pairs: Vec<_> = residues.into_iter().zip(moduli.iter().cloned()).collect();
+        assert_eq!(crt(&pairs), Some((x, basis.product().clone())));
+    }
+}
@@ -0,0 +1,215 @@
+use super::crt::crt;
+use super::gcd::modinv;
+use super::prime::splitmix64;
+use super::BigUint;
+
+use crate::std_alloc::Vec;
+
+use num_integer::Integer;
+use num_traits::{One, ToPrimitive, Zero};
+
+/// Subgroups of prime order up to this many bits are solved with baby-step
+/// giant-step, which needs a table of `sqrt(q)` elements, and larger ones with
+/// Pollard's rho.
+const BSGS_MAX_BITS: u64 = 32;
+
+/// Candidate solutions of the congruence in Pollard's rho are only tried when
+/// there are at most this many.
+const RHO_MAX_CANDIDATES: u32 = 1 << 16;
+
+/// Returns the least `x < n` with `g^x = h (mod p)` by baby-step giant-step,
+/// for `g` coprime to `p`.
+pub(super) fn bsgs(h: &BigUint, g: &BigUint, p: &BigUint, n: &BigUint) -> Option<BigUint> {
+    let m = n.sqrt() + 1u32;
+    let m_usize = m.to_usize().expect("memory overflow");
+
+    // baby steps g^j, sorted so that the least j comes first for equal powers
+    let mut table = Vec::with_capacity(m_usize);
+    let mut power = BigUint::one() % p;
+    for j in 0..m_usize {
+        let next = &power * g % p;
+        table.push((power, j));
+        power = next;
+    }
+    table.sort();
+    table.dedup_by(|a, b| a.0 == b.0);
+
+    // giant steps h g^(-im)
+    let giant = modinv(&power, p)?;
+    let mut y = h % p;
+    let mut i = BigUint::zero();
+    while i < m {
+        if let Ok(k) = table.binary_search_by(|(v, _)| v.cmp(&y)) {
+            let x = &i * &m + table[k].1;
+            return if x < *n { Some(x) } else { None };
+        }
+        y = y * &giant % p;
+        i += 1u32;
+    }
+    None
+}
+
+/// The number of multipliers in the random walk of Pollard's rho.
+const RHO_MULTIPLIERS: usize = 16;
+
+/// Returns some `x < n` with `g^x = h (mod p)` by Pollard's rho, where `n` is
+/// a multiple of the order of `g` and `p` is prime.
+pub(super) fn rho(h: &BigUint, g: &BigUint, p: &BigUint, n: &BigUint) -> Option<BigUint> {
+    let h = h % p;
+    if h.modpow(n, p) != BigUint::one() % p {
+        // `h` isn't in the subgroup generated by `g`.
+        return None;
+    }
+
+    let mut state = 0;
+    for _ in 0..8 {
+        // Teske's r-adding walk through x = g^a h^b, which multiplies by one
+        // of the random g^ai h^bi depending on the low digit of x.
+        let mut random = || BigUint::from(splitmix64(&mut state)) % n;
+        let multipliers: Vec<_> = (0..RHO_MULTIPLIERS)
+            .map(|_| {
+                let (a, b) = (random(), random());
+                let m = g.modpow(&a, p) * h.modpow(&b, p) % p;
+                (m, a, b)
+            })
+            .collect();
+        let step = |(x, a, b): &mut (BigUint, BigUint, BigUint)| {
+            let i = x.data.first().map_or(0, |&d| d as usize % RHO_MULTIPLIERS);
+            let (ref m, ref ai, ref bi) = multipliers[i];
+            *x = &*x * m % p;
+            *a = (&*a + ai) % n;
+            *b = (&*b + bi) % n;
+        };
+
+        // Brent's cycle detection, which moves the tortoise to the hare at
+        // every power of two steps.
+        let (a, b) = (random(), random());
+        let mut hare = (g.modpow(&a, p) * h.modpow(&b, p) % p, a, b);
+        let mut tortoise = hare.clone();
+        let mut steps = 0u64;
+        let mut power = 1;
+        loop {
+            step(&mut hare);
+            steps += 1;
+            if hare.0 == tortoise.0 {
+                break;
+            }
+            if steps == power {
+                tortoise = hare.clone();
+                power *= 2;
+                steps = 0;
+            }
+        }
+
+        // g^a1 h^b1 = g^a2 h^b2, so x (b1 - b2) = a2 - a1 (mod n)
+        let (_, a1, b1) = tortoise;
+        let (_, a2, b2) = hare;
+        let r = (b1 + n - b2) % n;
+        let s = (a2 + n - a1) % n;
+        let d = r.gcd(n);
+        if r.is_zero() || !(&s % &d).is_zero() || d > BigUint::from(RHO_MAX_CANDIDATES) {
+            continue;
+        }
+
+        // The solutions modulo n/d give d candidates modulo n.
+        let nd = n / &d;
+        let mut x = (&s / &d) * modinv(&(&r / &d), &nd).unwrap() % &nd;
+        while x < *n {
+            if g.modpow(&x, p) == h {
+                return Some(x);
+            }
+            x += &nd;
+        }
+    }
+    None
+}
+
+/// Solves `g^x = h (mod p)` in the subgroup of order `q`.
+fn subgroup_log(h: &BigUint, g: &BigUint, p: &BigUint, q: &BigUint) -> Option<BigUint> {
+    if q.bits() <= BSGS_MAX_BITS {
+        bsgs(h, g, p, q)
+    } else {
+        rho(h, g, p, q)
+    }
+}
+
+/// Returns the least `x` with `g^x = h (mod p)` by Pohlig–Hellman, where `factors`
+/// is the factorization of a multiple of the order of `g`.
+pub(super) fn pohlig_hellman(
+    h: &BigUint,
+    g: &BigUint,
+    p: &BigUint,
+    factors: &[(BigUint, u32)],
+) -> Option<BigUint> {
+    let h = h % p;
+    let g = g % p;
+    if h == BigUint::one() % p {
+        return Some(BigUint::zero());
+    }
+    if g.is_zero() {
+        return if h.is_zero() {
+            Some(BigUint::one())
+        } else {
+            None
+        };
+    }
+    let g_inv = modinv(&g, p)?;
+
+    // Reduce the factorization to that of the actual order of g, so that the
+    // logarithms in each subgroup are unique.
+    let mut order: BigUint = factors.iter().map(|(q, e)| q.pow(*e)).product();
+    let mut factors = factors.to_vec();
+    for (q, e) in &mut factors {
+        while *e > 0 && g.modpow(&(&order / &*q), p).is_one() {
+            order /= &*q;
+            *e -= 1;
+        }
+    }
+    factors.retain(|&(_, e)| e > 0);
+
+    // Find x modulo each q^e, one base q digit at a time, from the logarithms
+    // in the subgroup of order q.
+    let mut congruences = Vec::with_capacity(factors.len());
+    for (q, e) in &factors {
+        let qe = q.pow(*e);
+        let cofactor = &order / &qe;
+        let gq = g.modpow(&cofactor, p);
+        let gq_inv = g_inv.modpow(&cofactor, p);
+        let hq = h.modpow(&cofactor, p);
+        let gamma = gq.modpow(&q.pow(e - 1), p);
+
+        let mut x = BigUint::zero();
+        let mut qk = BigUint::one();
+        for k in 0..*e {
+            let hk = (gq_inv.modpow(&x, p) * &hq % p).modpow(&q.pow(e - 1 - k), p);
+            let digit = subgroup_log(&hk, &gamma, p, q)?;
+            x += digit * &qk;
+            qk *= q;
+        }
+        congruences.push((x, qe));
+    }
+
+    // The solution modulo the order of g is the least one.
+    let (x, _) = crt(&congruences)?;
+    if g.modpow(&x, p) == h {
+        Some(x)
+    } else {
+        None
+    }
+}
+
+#[test]
+fn test_bsgs_rho() {
+    let p = BigUint::from(1_000_003u32);
+    let g = BigUint::from(2u32);
+    let n = &p - 1u32;
+    for &x in &[0u32, 1, 2, 1000, 500_000, 1_000_001] {
+        let x = BigUint::from(x);
+        let h = g.modpow(&x, &p);
+        let found = bsgs(&h, &g, &p, &n).unwrap();
+        assert_eq!(g.modpow(&found, &p), h);
+
+        let found = rho(&h, &g, &p, &n).unwrap();
+        assert_eq!(g.modpow(&found, &p), h);
+    }
+}
@@ -1,9 +1,10 @@
 use super::addition::__add2;
 #[cfg(not(u64_digit))]
 use super::u32_to_u128;
-use super::{cmp_slice, BigUint};
+use super::{biguint_from_vec, cmp_slice, BigUint};
 
 use crate::big_digit::{self, BigDigit, DoubleBigDigit};
+use crate::std_alloc::Vec;
 use crate::UsizePromotion;
 
 use core::cmp::Ordering::{Equal, Greater, Less};
@@ -65,7 +66,7 @@ pub(super) fn div_rem_digit(mut a: BigUint, b: BigDigit) -> (BigUint, BigDigit)
 }
 
 #[inline]
-fn rem_digit(a: &BigUint, b: BigDigit) -> BigDigit {
+pub(super) fn rem_digit(a: &BigUint, b: BigDigit) -> BigDigit {
     if b == 0 {
         panic!("attempt to divide by zero")
     }
@@ -155,9 +156,9 @@ fn div_rem(mut u: BigUint, mut d: BigUint) -> (BigUint, BigUint) {
 
     if shift == 0 {
         // no need to clone d
-        div_rem_core(u, &d.data)
+        div_rem_normalized(u, &d.data)
     } else {
-        let (q, r) = div_rem_core(u << shift, &(d << shift).data);
+        let (q, r) = div_rem_normalized(u << shift, &(d << shift).data);
         // renormalize the remainder
         (q, r >> shift)
     }
@@ -197,14 +198,160 @@ pub(super) fn div_rem_ref(u: &BigUint, d: &BigUint) -> (BigUint, BigUint) {
 
     if shift == 0 {
         // no need to clone d
-        div_rem_core(u.clone(), &d.data)
+        div_rem_normalized(u.clone(), &d.data)
     } else {
-        let (q, r) = div_rem_core(u << shift, &(d << shift).data);
+        let (q, r) = div_rem_normalized(u << shift, &(d << shift).data);
         // renormalize the remainder
         (q, r >> shift)
     }
 }
 
+/// The length in digits at which we switch from the base division algorithm to the
+/// recursive algorithm of Burnikel and Ziegler, when both the divisor and the quotient
+/// are at least this long. Below this, it's also the base case of the recursion.
+pub(super) const BURNIKEL_ZIEGLER_THRESHOLD: usize = 64;
+
+/// Divides by a divisor whose highest bit is set, choosing the algorithm by size.
+fn div_rem_normalized(a: BigUint, b: &[BigDigit]) -> (BigUint, BigUint) {
+    if b.len() < BURNIKEL_ZIEGLER_THRESHOLD || a.data.len() - b.len() < BURNIKEL_ZIEGLER_THRESHOLD {
+        div_rem_core(a, b)
+    } else {
+        div_rem_burnikel_ziegler(a, b)
+    }
+}
+
+/// Splits the digits of `x` into those below digit `n`, and those from digit `n` onward.
+fn split_digits(x: &[BigDigit], n: usize) -> (&[BigDigit], &[BigDigit]) {
+    if x.len() <= n {
+        (x, &[])
+    } else {
+        x.split_at(n)
+    }
+}
+
+/// Returns `hi << (n * BITS) | lo`, where `lo` has at most `n` digits.
+fn join_digits(lo: &[BigDigit], hi: &[BigDigit], n: usize) -> BigUint {
+    debug_assert!(lo.len() <= n);
+    let mut data = Vec::with_capacity(n + hi.len());
+    data.extend_from_slice(lo);
+    if !hi.is_empty() {
+        data.resize(n, 0);
+        data.extend_from_slice(hi);
+    }
+    biguint_from_vec(data)
+}
+
+/// Recursive division, from Burnikel and Ziegler, "Fast Recursive Division",
+/// MPI-I-98-1-022, 1998.
+///
+/// The divisor must have its highest bit set, and be at least two digits long.
+fn div_rem_burnikel_ziegler(a: BigUint, b: &[BigDigit]) -> (BigUint, BigUint) {
+    let n = b.len();
+    let q_len = a.data.len() + 1 - n;
+
+    if q_len + 1 < n {
+        // When the quotient is much shorter than the divisor, the low digits of the divisor
+        // barely affect it. Dividing by just the top `q_len + 1` digits of the divisor gives
+        // an estimate which is never too small, and too large by at most 2.
+        let k = n - q_len - 1;
+        let (mut q, _) = div_rem_normalized(biguint_from_vec(a.data[k..].to_vec()), &b[k..]);
+        let b = biguint_from_vec(b.to_vec());
+        let mut prod = &q * &b;
+        while prod > a {
+            q -= 1u32;
+            prod -= &b;
+        }
+        return (q, a - prod);
+    }
+
+    // Pad the divisor to a length `j * m`, where `m` is a power of two and `j` is at most the
+    // threshold, so that it can be halved evenly until it reaches the base case. Shifting both
+    // operands by the same number of digits doesn't change the quotient.
+    let mut m = 1;
+    while n > BURNIKEL_ZIEGLER_THRESHOLD * m {
+        m <<= 1;
+    }
+    let block = Integer::div_ceil(&n, &m) * m;
+    let pad = block - n;
+    let b = join_digits(&[], b, pad);
+    let a = join_digits(&[], &a.data, pad);
+
+    // Now divide by schoolbook long division, treating every `block` digits as one big digit.
+    let blocks = Integer::div_ceil(&a.data.len(), &block);
+    let mut q = vec![0; blocks * block];
+    let mut r = BigUint::zero();
+    for i in (0..blocks).rev() {
+        let (lo, _) = split_digits(&a.data[i * block..], block);
+        let (qi, ri) = div_two_by_one(join_digits(lo, &r.data, block), &b, block);
+        q[i * block..i * block + qi.data.len()].copy_from_slice(&qi.data);
+        r = ri;
+    }
+
+    let (_, r) = split_digits(&r.data, pad);
+    (biguint_from_vec(q), biguint_from_vec(r.to_vec()))
+}
+
+/// Divides `a` by the `n`-digit divisor `b` with its highest bit set, where the quotient
+/// is known to fit in `n` digits, i.e. `a < b << (n * BITS)`.
+///
+/// This is algorithm 1 of Burnikel and Ziegler.
+fn div_two_by_one(a: BigUint, b: &BigUint, n: usize) -> (BigUint, BigUint) {
+    debug_assert_eq!(b.data.len(), n);
+
+    if n % 2 == 1 || n <= BURNIKEL_ZIEGLER_THRESHOLD {
+        return if a < *b {
+            (Zero::zero(), a)
+        } else {
+            div_rem_core(a, &b.data)
+        };
+    }
+
+    // a = [a1, a2, a3, a4] and b = [b1, b2], with each part of `n / 2` digits.
+    let half = n / 2;
+    let (a34, a12) = split_digits(&a.data, n);
+    let (a4, a3) = split_digits(a34, half);
+    let (b2, b1) = split_digits(&b.data, half);
+    let b1 = biguint_from_vec(b1.to_vec());
+    let b2 = biguint_from_vec(b2.to_vec());
+
+    let a12 = biguint_from_vec(a12.to_vec());
+    let (q1, r) = div_three_by_two(a12, a3, b, &b1, &b2, half);
+    let (q2, r) = div_three_by_two(r, a4, b, &b1, &b2, half);
+    (join_digits(&q2.data, &q1.data, half), r)
+}
+
+/// Divides `a12 << (n * BITS) | a3` by the `2n`-digit divisor `b = b1 << (n * BITS) | b2`
+/// with its highest bit set, where the quotient is known to fit in `n` digits.
+///
+/// This is algorithm 2 of Burnikel and Ziegler.
+fn div_three_by_two(
+    a12: BigUint,
+    a3: &[BigDigit],
+    b: &BigUint,
+    b1: &BigUint,
+    b2: &BigUint,
+    n: usize,
+) -> (BigUint, BigUint) {
+    let (mut q, r) = if a12.data.len() > n && a12.data[n..] == b1.data[..] {
+        // The estimate a12 / b1 would overflow `n` digits, but the quotient can't,
+        // so use the largest possible value instead: r = a12 - q * b1
+        let q = biguint_from_vec(vec![big_digit::MAX; n]);
+        let r = biguint_from_vec(a12.data[..n].to_vec()) + b1;
+        (q, r)
+    } else {
+        div_two_by_one(a12, b1, n)
+    };
+
+    // The estimate is never too small, and too large by at most 2.
+    let d = &q * b2;
+    let mut r = join_digits(a3, &r.data, n);
+    while r < d {
+        q -= 1u32;
+        r += b;
+    }
+    (q, r - d)
+}
+
 /// An implementation of the base division algorithm.
 /// Knuth, TAOCP vol 2 section 4.3.1, algorithm D, with an improvement from exercises 19-21.
 fn div_rem_core(mut a: BigUint, b: &[BigDigit]) -> (BigUint, BigUint) {
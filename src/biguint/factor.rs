@@ -0,0 +1,468 @@
+use super::division::rem_digit;
+use super::factorial::primes_up_to;
+use super::gcd::modinv;
+use super::monty::{MontgomeryContext, MontgomeryElement};
+use super::BigUint;
+
+use crate::big_digit::BigDigit;
+use crate::std_alloc::Vec;
+
+use core::cmp;
+
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+/// Integer factorization by trial division, Pollard's rho (Brent's variant),
+/// Pollard's `p - 1` and the elliptic curve method (ECM), in that order, each
+/// within a configurable effort limit.
+///
+/// Setting a limit to zero skips that method. Any composite parts that are left
+/// when all the methods have given up are returned as cofactors of the
+/// [`Factorization`](struct.Factorization.html).
+///
+/// # Examples
+///
+/// ```
+/// use num_bigint::{BigUint, Factorizer};
+///
+/// let n = BigUint::from(2u32).pow(64) + 1u32;
+/// let factorization = Factorizer::new().factor(&n);
+/// assert!(factorization.is_complete());
+///
+/// let factors: Vec<_> = factorization.factors().iter().map(|(p, _)| p.to_string()).collect();
+/// assert_eq!(factors, ["274177", "67280421310721"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Factorizer {
+    trial_bound: u32,
+    rho_iterations: u64,
+    pm1_bound: u64,
+    ecm_curves: u32,
+    ecm_bound: u64,
+}
+
+/// The prime factorization of a number, found by a
+/// [`Factorizer`](struct.Factorizer.html).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Factorization {
+    factors: Vec<(BigUint, u32)>,
+    cofactors: Vec<(BigUint, u32)>,
+}
+
+impl Default for Factorizer {
+    fn default() -> Self {
+        Factorizer {
+            trial_bound: 1 << 12,
+            rho_iterations: 1 << 16,
+            pm1_bound: 10_000,
+            ecm_curves: 20,
+            ecm_bound: 2_000,
+        }
+    }
+}
+
+impl Factorizer {
+    /// Creates a factorizer with the default limits, which are meant to find
+    /// factors of up to about 15 digits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the bound for trial division by the primes up to `bound`.
+    pub fn trial_division(mut self, bound: u32) -> Self {
+        self.trial_bound = bound;
+        self
+    }
+
+    /// Sets the number of iterations of Pollard's rho for each composite.
+    pub fn pollard_rho(mut self, iterations: u64) -> Self {
+        self.rho_iterations = iterations;
+        self
+    }
+
+    /// Sets the smoothness bound of Pollard's `p - 1`.
+    pub fn pollard_pm1(mut self, bound: u64) -> Self {
+        self.pm1_bound = bound;
+        self
+    }
+
+    /// Sets the number of curves for each composite and the smoothness bound
+    /// of the elliptic curve method.
+    pub fn ecm(mut self, curves: u32, bound: u64) -> Self {
+        self.ecm_curves = curves;
+        self.ecm_bound = bound;
+        self
+    }
+
+    /// Factors `n` as far as the limits allow.
+    ///
+    /// Panics if `n` is zero.
+    pub fn factor(&self, n: &BigUint) -> Factorization {
+        assert!(!n.is_zero(), "cannot factor zero");
+        let mut factors = Vec::new();
+        let mut cofactors = Vec::new();
+
+        let twos = n.trailing_zeros().unwrap();
+        if twos > 0 {
+            factors.push((BigUint::from(2u32), twos as u32));
+        }
+        let mut n = n >> twos;
+        self.trial_divide(&mut n, &mut factors);
+
+        let max_bound = cmp::max(self.pm1_bound, self.ecm_bound);
+        let primes = if self.ecm_curves > 0 || self.pm1_bound > 0 {
+            primes_up_to(max_bound)
+        } else {
+            Vec::new()
+        };
+
+        let mut stack = vec![(n, 1)];
+        while let Some((m, k)) = stack.pop() {
+            if m.is_one() {
+                continue;
+            }
+            if m.is_probably_prime(0) {
+                factors.push((m, k));
+            } else if let Some((base, e)) = m.is_perfect_power() {
+                stack.push((base, k * e));
+            } else if let Some(d) = self.split(&m, &primes) {
+                let q = &m / &d;
+                stack.push((d, k));
+                stack.push((q, k));
+            } else {
+                cofactors.push((m, k));
+            }
+        }
+
+        Factorization {
+            factors: merge(factors),
+            cofactors: merge(cofactors),
+        }
+    }
+
+    /// Removes the odd prime factors up to the trial division bound from `n`.
+    fn trial_divide(&self, n: &mut BigUint, factors: &mut Vec<(BigUint, u32)>) {
+        for p in primes_up_to(u64::from(self.trial_bound))
+            .into_iter()
+            .skip(1)
+        {
+            if BigUint::from(p * p) > *n {
+                // Whatever is left is prime, or one.
+                break;
+            }
+            let mut k = 0;
+            while rem_digit(n, p as BigDigit) == 0 {
+                *n /= p;
+                k += 1;
+            }
+            if k > 0 {
+                factors.push((BigUint::from(p), k));
+            }
+        }
+    }
+
+    /// Finds a non-trivial divisor of the odd composite `n`, which is not
+    /// a perfect power.
+    fn split(&self, n: &BigUint, primes: &[u64]) -> Option<BigUint> {
+        let ctx = MontgomeryContext::new(n);
+
+        // A cycle without a factor needs another polynomial.
+        let mut budget = self.rho_iterations;
+        let mut c = 1u32;
+        while budget > 0 {
+            if let Some(d) = pollard_rho(&ctx, &ctx.to_monty(&BigUint::from(c)), &mut budget) {
+                return Some(d);
+            }
+            c += 1;
+        }
+
+        if self.pm1_bound > 0 {
+            let end = primes.iter().take_while(|&&p| p <= self.pm1_bound).count();
+            if let Some(d) = pollard_pm1(&ctx, &primes[..end], self.pm1_bound) {
+                return Some(d);
+            }
+        }
+
+        let end = primes.iter().take_while(|&&p| p <= self.ecm_bound).count();
+        for sigma in 6..6 + u64::from(self.ecm_curves) {
+            if let Some(d) = ecm(&ctx, sigma, &primes[..end], self.ecm_bound) {
+                return Some(d);
+            }
+        }
+        None
+    }
+}
+
+/// Sorts the factors and combines the multiplicities of equal ones.
+fn merge(mut factors: Vec<(BigUint, u32)>) -> Vec<(BigUint, u32)> {
+    factors.sort();
+    let mut merged: Vec<(BigUint, u32)> = Vec::with_capacity(factors.len());
+    for (p, k) in factors {
+        match merged.last_mut() {
+            Some(last) if last.0 == p => last.1 += k,
+            _ => merged.push((p, k)),
+        }
+    }
+    merged
+}
+
+impl Factorization {
+    /// Returns the prime factors in increasing order, with their multiplicities.
+    ///
+    /// The factors are probable primes by the Baillie–PSW test -- see
+    /// [`BigUint::is_probably_prime`](struct.BigUint.html#method.is_probably_prime).
+    pub fn factors(&self) -> &[(BigUint, u32)] {
+        &self.factors
+    }
+
+    /// Returns the composite parts that couldn't be factored within the limits,
+    /// in increasing order, with their multiplicities.
+    pub fn cofactors(&self) -> &[(BigUint, u32)] {
+        &self.cofactors
+    }
+
+    /// Returns `true` if the number was factored completely into primes.
+    pub fn is_complete(&self) -> bool {
+        self.cofactors.is_empty()
+    }
+}
+
+/// Returns `gcd(x, n)` for a value in Montgomery form, which is the same as
+/// for the regular value since the Montgomery factor is coprime to `n`.
+fn monty_gcd(ctx: &MontgomeryContext, x: &MontgomeryElement) -> BigUint {
+    ctx.from_monty(x).gcd(ctx.modulus())
+}
+
+/// Returns the divisor of `n` found in `g`, unless it's trivial.
+fn proper_divisor(ctx: &MontgomeryContext, g: BigUint) -> Option<BigUint> {
+    if g.is_one() || g == *ctx.modulus() {
+        None
+    } else {
+        Some(g)
+    }
+}
+
+/// Pollard's rho with Brent's cycle detection, iterating `x^2 + c`, with the
+/// differences multiplied together so that only every `BATCH`th step takes
+/// a gcd. This spends at most `budget` iterations, and stops early if the
+/// sequence cycles modulo `n` itself.
+fn pollard_rho(
+    ctx: &MontgomeryContext,
+    c: &MontgomeryElement,
+    budget: &mut u64,
+) -> Option<BigUint> {
+    const BATCH: u64 = 128;
+    let f = |x: &MontgomeryElement| ctx.add(&ctx.square(x), c);
+
+    let mut y = ctx.to_monty(&BigUint::from(2u32));
+    let mut q = ctx.one();
+    let mut r = 1;
+    loop {
+        let x = y.clone();
+        for _ in 0..r {
+            y = f(&y);
+        }
+        *budget = budget.saturating_sub(r);
+
+        let mut k = 0;
+        while k < r {
+            let ys = y.clone();
+            let steps = cmp::min(BATCH, r - k);
+            for _ in 0..steps {
+                y = f(&y);
+                q = ctx.mul(&q, &ctx.sub(&x, &y));
+            }
+            *budget = budget.saturating_sub(steps);
+
+            let g = monty_gcd(ctx, &q);
+            if !g.is_one() {
+                if g != *ctx.modulus() {
+                    return Some(g);
+                }
+                // The whole batch was too much, so retrace it one step at a time.
+                let mut ys = ys;
+                for _ in 0..steps {
+                    ys = f(&ys);
+                    let g = monty_gcd(ctx, &ctx.sub(&x, &ys));
+                    if !g.is_one() {
+                        return proper_divisor(ctx, g);
+                    }
+                }
+                return None;
+            }
+            if *budget == 0 {
+                return None;
+            }
+            k += steps;
+        }
+        r *= 2;
+    }
+}
+
+/// Returns the largest power of the prime `p` up to `bound`.
+fn prime_power(p: u64, bound: u64) -> u64 {
+    let mut q = p;
+    while q <= bound / p {
+        q *= p;
+    }
+    q
+}
+
+/// Pollard's `p - 1`, which finds the prime factors `p` of `n` for which
+/// `p - 1` is a product of prime powers up to `bound`.
+fn pollard_pm1(ctx: &MontgomeryContext, primes: &[u64], bound: u64) -> Option<BigUint> {
+    const BATCH: usize = 256;
+    let one = ctx.one();
+    let mut a = ctx.to_monty(&BigUint::from(2u32));
+    for (i, chunk) in primes.chunks(BATCH).enumerate() {
+        let start = a.clone();
+        for &p in chunk {
+            a = ctx.pow(&a, &BigUint::from(prime_power(p, bound)));
+        }
+        let g = monty_gcd(ctx, &ctx.sub(&a, &one));
+        if g.is_one() {
+            continue;
+        }
+        if g != *ctx.modulus() {
+            return Some(g);
+        }
+
+        // All the factors turned up in this batch, so retry it one prime at a time.
+        let mut a = start;
+        for &p in &primes[i * BATCH..i * BATCH + chunk.len()] {
+            a = ctx.pow(&a, &BigUint::from(prime_power(p, bound)));
+            let g = monty_gcd(ctx, &ctx.sub(&a, &one));
+            if !g.is_one() {
+                return proper_divisor(ctx, g);
+            }
+        }
+        return None;
+    }
+    None
+}
+
+/// A point `(X : Z)` on a Montgomery curve, in projective coordinates without `Y`.
+#[derive(Clone)]
+struct Point {
+    x: MontgomeryElement,
+    z: MontgomeryElement,
+}
+
+/// Arithmetic on the Montgomery curve `B y^2 = x^3 + A x^2 + x` modulo `n`.
+struct Curve<'a> {
+    ctx: &'a MontgomeryContext,
+    /// (A + 2) / 4
+    a24: MontgomeryElement,
+}
+
+impl<'a> Curve<'a> {
+    fn double(&self, p: &Point) -> Point {
+        let ctx = self.ctx;
+        let sum = ctx.square(&ctx.add(&p.x, &p.z));
+        let diff = ctx.square(&ctx.sub(&p.x, &p.z));
+        let t = ctx.sub(&sum, &diff);
+        Point {
+            x: ctx.mul(&sum, &diff),
+            z: ctx.mul(&t, &ctx.add(&diff, &ctx.mul(&self.a24, &t))),
+        }
+    }
+
+    /// Returns `p + q`, given their difference.
+    fn add(&self, p: &Point, q: &Point, diff: &Point) -> Point {
+        let ctx = self.ctx;
+        let u = ctx.mul(&ctx.sub(&p.x, &p.z), &ctx.add(&q.x, &q.z));
+        let v = ctx.mul(&ctx.add(&p.x, &p.z), &ctx.sub(&q.x, &q.z));
+        Point {
+            x: ctx.mul(&diff.z, &ctx.square(&ctx.add(&u, &v))),
+            z: ctx.mul(&diff.x, &ctx.square(&ctx.sub(&u, &v))),
+        }
+    }
+
+    /// Returns `k p` for `k >= 1` by the Montgomery ladder.
+    fn mul(&self, p: &Point, k: u64) -> Point {
+        let mut r0 = p.clone();
+        let mut r1 = self.double(p);
+        for i in (0..63 - k.leading_zeros()).rev() {
+            if (k >> i) & 1 == 1 {
+                r0 = self.add(&r0, &r1, p);
+                r1 = self.double(&r1);
+            } else {
+                r1 = self.add(&r0, &r1, p);
+                r0 = self.double(&r0);
+            }
+        }
+        r0
+    }
+}
+
+/// One curve of the elliptic curve method, stage 1, which finds the prime
+/// factors `p` of `n` for which the order of the curve modulo `p` is a product
+/// of prime powers up to `bound`. The curve and point are chosen from `sigma`
+/// by Suyama's parametrization, which makes the order a multiple of 12.
+fn ecm(ctx: &MontgomeryContext, sigma: u64, primes: &[u64], bound: u64) -> Option<BigUint> {
+    let n = ctx.modulus();
+    let sigma = ctx.to_monty(&BigUint::from(sigma));
+    let five = ctx.to_monty(&BigUint::from(5u32));
+
+    // u = sigma^2 - 5, v = 4 sigma, and the point is (u^3 : v^3)
+    let u = ctx.sub(&ctx.square(&sigma), &five);
+    let v = ctx.add(&ctx.add(&sigma, &sigma), &ctx.add(&sigma, &sigma));
+    let u3 = ctx.mul(&ctx.square(&u), &u);
+    let v3 = ctx.mul(&ctx.square(&v), &v);
+
+    // (A + 2) / 4 = (v - u)^3 (3u + v) / (16 u^3 v)
+    let v_u = ctx.sub(&v, &u);
+    let num = ctx.mul(
+        &ctx.mul(&ctx.square(&v_u), &v_u),
+        &ctx.add(&ctx.add(&u, &u), &ctx.add(&u, &v)),
+    );
+    let den = ctx.from_monty(&ctx.mul(&u3, &v)) << 4u8;
+    let inv = match modinv(&den, n) {
+        Some(inv) => inv,
+        None => return proper_divisor(ctx, den.gcd(n)),
+    };
+    let curve = Curve {
+        ctx,
+        a24: ctx.mul(&num, &ctx.to_monty(&inv)),
+    };
+
+    let mut point = Point { x: u3, z: v3 };
+    for &p in primes {
+        point = curve.mul(&point, prime_power(p, bound));
+    }
+    proper_divisor(ctx, monty_gcd(ctx, &point.z))
+}
+
+#[test]
+fn test_pollard_rho() {
+    // 2^64 + 1 = 274177 * 67280421310721
+    let n = (BigUint::one() << 64u8) + 1u32;
+    let ctx = MontgomeryContext::new(&n);
+    let mut budget = 1 << 16;
+    let d = pollard_rho(&ctx, &ctx.one(), &mut budget).unwrap();
+    assert!(d == BigUint::from(274177u32) || d == BigUint::from(67280421310721u64));
+}
+
+#[test]
+fn test_pollard_pm1() {
+    // p - 1 = 2^4 3^2 5 7 11 13 17 19, and q - 1 = 2 500000003
+    let p = BigUint::from(232_792_561u32);
+    let q = BigUint::from(1_000_000_007u32);
+    let n = &p * &q;
+    let ctx = MontgomeryContext::new(&n);
+    let primes = primes_up_to(100);
+    assert_eq!(pollard_pm1(&ctx, &primes, 100), Some(p));
+    assert_eq!(pollard_pm1(&ctx, &primes[..4], 10), None);
+}
+
+#[test]
+fn test_ecm() {
+    // two 40-bit primes
+    let p = BigUint::from(1_099_511_627_791u64);
+    let q = BigUint::from(1_099_511_628_401u64);
+    let n = &p * &q;
+    let ctx = MontgomeryContext::new(&n);
+    let primes = primes_up_to(2000);
+    let d = (6..100).find_map(|sigma| ecm(&ctx, sigma, &primes, 2000));
+    assert!(d == Some(p) || d == Some(q));
+}
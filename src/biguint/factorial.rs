@@ -0,0 +1,166 @@
+use super::BigUint;
+
+use crate::std_alloc::Vec;
+
+use num_traits::{One, ToPrimitive, Zero};
+
+/// Returns the primes up to `n`, by the sieve of Eratosthenes over odd numbers.
+pub(super) fn primes_up_to(n: u64) -> Vec<u64> {
+    if n < 2 {
+        return Vec::new();
+    }
+    let len = (n.to_usize().expect("memory overflow") - 1) / 2 + 1;
+    // sieve[i] tells whether 2i + 1 is composite
+    let mut sieve = vec![false; len];
+    let mut primes = vec![2];
+    for i in 1..len {
+        if !sieve[i] {
+            let p = 2 * i + 1;
+            primes.push(p as u64);
+            let mut j = match p.checked_mul(p) {
+                Some(square) => square / 2,
+                None => len,
+            };
+            while j < len {
+                sieve[j] = true;
+                j += p;
+            }
+        }
+    }
+    primes
+}
+
+/// Returns the exponent of the prime `p` in `n!`, by Legendre's formula.
+fn factorial_valuation(mut n: u64, p: u64) -> u64 {
+    let mut e = 0;
+    while n >= p {
+        n /= p;
+        e += n;
+    }
+    e
+}
+
+/// Multiplies `xs` by binary splitting, so that the large multiplications
+/// are balanced and can use the faster algorithms.
+fn product_tree(xs: &[u64]) -> BigUint {
+    match xs.len() {
+        0 => BigUint::one(),
+        1 => BigUint::from(xs[0]),
+        n => product_tree(&xs[..n / 2]) * product_tree(&xs[n / 2..]),
+    }
+}
+
+/// Returns the product of the non-zero values, first packing as many of them
+/// as fit into each `u64`.
+fn product<I: IntoIterator<Item = u64>>(iter: I) -> BigUint {
+    let mut packed = Vec::new();
+    let mut acc = 1u64;
+    for x in iter {
+        match acc.checked_mul(x) {
+            Some(y) => acc = y,
+            None => {
+                packed.push(acc);
+                acc = x;
+            }
+        }
+    }
+    packed.push(acc);
+    product_tree(&packed)
+}
+
+/// Returns the product of `p^e` for the primes `p` up to `n`, where `e` is
+/// given by `exponent(p)`.
+///
+/// Writing each exponent in binary, this is `∏ P_i^(2^i)`, where `P_i` is the
+/// product of the primes whose exponent has bit `i` set, evaluated by repeated
+/// squaring. The power of two is just a shift.
+fn prime_power_product<F: Fn(u64) -> u64>(n: u64, exponent: F) -> BigUint {
+    let primes = primes_up_to(n);
+    if primes.is_empty() {
+        return BigUint::one();
+    }
+    let twos = exponent(2);
+    let odd: Vec<(u64, u64)> = primes[1..]
+        .iter()
+        .map(|&p| (p, exponent(p)))
+        .filter(|&(_, e)| e != 0)
+        .collect();
+
+    let max = odd.iter().map(|&(_, e)| e).max().unwrap_or(0);
+    let mut acc = BigUint::one();
+    for i in (0..64 - max.leading_zeros()).rev() {
+        acc = acc.square();
+        let bit = odd.iter().filter(|&&(_, e)| e >> i & 1 == 1);
+        acc *= product(bit.map(|&(p, _)| p));
+    }
+    acc << twos
+}
+
+pub(super) fn factorial(n: u64) -> BigUint {
+    prime_power_product(n, |p| factorial_valuation(n, p))
+}
+
+pub(super) fn double_factorial(n: u64) -> BigUint {
+    let k = n / 2;
+    if n & 1 == 0 {
+        // (2k)!! = 2^k k!
+        factorial(k) << k
+    } else {
+        // (2k + 1)!! = (2k + 1)! / (2^k k!)
+        prime_power_product(n, |p| {
+            if p == 2 {
+                0
+            } else {
+                factorial_valuation(n, p) - factorial_valuation(k, p)
+            }
+        })
+    }
+}
+
+pub(super) fn binomial(n: u64, k: u64) -> BigUint {
+    if k > n {
+        return BigUint::zero();
+    }
+    let k = if k > n - k { n - k } else { k };
+    if k < 32 || n / 64 > k {
+        // With few factors, sieving all the primes up to `n` isn't worth it.
+        product(n - k + 1..=n) / factorial(k)
+    } else {
+        prime_power_product(n, |p| {
+            factorial_valuation(n, p) - factorial_valuation(k, p) - factorial_valuation(n - k, p)
+        })
+    }
+}
+
+pub(super) fn multinomial(ks: &[u64]) -> BigUint {
+    let n = ks
+        .iter()
+        .try_fold(0u64, |n, &k| n.checked_add(k))
+        .expect("multinomial overflow");
+    prime_power_product(n, |p| {
+        let denominator: u64 = ks.iter().map(|&k| factorial_valuation(k, p)).sum();
+        factorial_valuation(n, p) - denominator
+    })
+}
+
+pub(super) fn primorial(n: u64) -> BigUint {
+    product(primes_up_to(n))
+}
+
+#[test]
+fn test_primes_up_to() {
+    assert!(primes_up_to(0).is_empty());
+    assert!(primes_up_to(1).is_empty());
+    assert_eq!(primes_up_to(2), [2]);
+    assert_eq!(primes_up_to(30), [2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+    assert_eq!(primes_up_to(1 << 16).len(), 6542);
+}
+
+#[test]
+fn test_product() {
+    for n in 0u64..200 {
+        let expected = (1..=n).fold(BigUint::one(), |acc, x| acc * x);
+        assert_eq!(product(1..=n), expected);
+        assert_eq!(factorial(n), expected);
+    }
+}
@@ -0,0 +1,602 @@
+use super::{biguint_from_vec, BigUint};
+
+use crate::big_digit::{self, BigDigit, DoubleBigDigit};
+use crate::BigInt;
+
+use core::cmp;
+use core::mem;
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+
+/// The number of digits at which `gcd` switches from Stein's binary algorithm
+/// to Lehmer's algorithm.
+pub(super) const LEHMER_THRESHOLD: usize = 2;
+
+/// The number of digits at which the half-GCD recursion starts, and below which
+/// `gcd` uses only Lehmer's algorithm.
+const HGCD_THRESHOLD: usize = 64;
+
+/// The cosequence of a run of Euclidean steps simulated on leading digits.
+///
+/// Only magnitudes are stored: after an even number of steps `u0, v1 >= 0`
+/// and `u1, v0 <= 0`, and after an odd number of steps the signs are flipped.
+struct Cosequence {
+    u0: BigDigit,
+    u1: BigDigit,
+    v0: BigDigit,
+    v1: BigDigit,
+    even: bool,
+}
+
+/// Simulates Euclidean steps on the leading digit of `a` and `b`, using
+/// Collins' condition to stop while the quotients are still known to be exact.
+/// See Jebelean, "Improving the multiprecision Euclidean algorithm".
+///
+/// The steps also stop before the smaller remainder, scaled to the leading digit
+/// of `a`, could drop below `min`.
+///
+/// Requires `a >= b` and `b.len() >= 2`.
+#[allow(clippy::many_single_char_names)]
+fn lehmer_simulate(a: &[BigDigit], b: &[BigDigit], min: BigDigit) -> Cosequence {
+    let n = a.len();
+    let m = b.len();
+    debug_assert!(n >= m && m >= 2);
+
+    // extract the top digit of bits from a, and the same bits from b
+    let h = a[n - 1].leading_zeros() as u8;
+    let top = |hi: BigDigit, lo: BigDigit| {
+        if h == 0 {
+            hi
+        } else {
+            hi << h | lo >> (big_digit::BITS - h)
+        }
+    };
+    let mut a1 = top(a[n - 1], a[n - 2]);
+    let mut a2 = if n == m {
+        top(b[n - 1], b[n - 2])
+    } else if n == m + 1 {
+        top(0, b[n - 2])
+    } else {
+        0
+    };
+
+    // The cosequence values are bounded by the input digits, so they can't overflow.
+    let (mut u0, mut u1, mut u2) = (0, 1, 0);
+    let (mut v0, mut v1, mut v2) = (0, 0, 1);
+    let mut even = false;
+    while a2 >= v2 && a2 - v2 >= min && a1 - a2 >= v1 + v2 {
+        let (q, r) = (a1 / a2, a1 % a2);
+        a1 = a2;
+        a2 = r;
+        let u = u1 + q * u2;
+        u0 = mem::replace(&mut u1, mem::replace(&mut u2, u));
+        let v = v1 + q * v2;
+        v0 = mem::replace(&mut v1, mem::replace(&mut v2, v));
+        even = !even;
+    }
+
+    Cosequence {
+        u0,
+        u1,
+        v0,
+        v1,
+        even,
+    }
+}
+
+/// Applies a simulated cosequence to `a` and `b` in place:
+/// `(a, b) = (u0 * a + v0 * b, u1 * a + v1 * b)`, with signs as described in `Cosequence`.
+/// Both results are known to be non-negative and no larger than `a`.
+fn lehmer_update(a: &mut BigUint, b: &mut BigUint, cs: &Cosequence) {
+    let n = a.data.len();
+    b.data.resize(n, 0);
+
+    // Each result is the difference of two digit-by-vector products, tracked with
+    // separate carries for each product and a borrow for the difference.
+    let (mut ca_a, mut ca_b, mut ba) = (0, 0, false);
+    let (mut cb_a, mut cb_b, mut bb) = (0, 0, false);
+    for (ai, bi) in a.data.iter_mut().zip(b.data.iter_mut()) {
+        let (x, y) = (DoubleBigDigit::from(*ai), DoubleBigDigit::from(*bi));
+
+        let pa = x * DoubleBigDigit::from(cs.u0) + ca_a;
+        let pb = y * DoubleBigDigit::from(cs.v0) + ca_b;
+        ca_a = pa >> big_digit::BITS;
+        ca_b = pb >> big_digit::BITS;
+        let (pos, neg) = if cs.even { (pa, pb) } else { (pb, pa) };
+        let (d, b1) = (pos as BigDigit).overflowing_sub(neg as BigDigit);
+        let (d, b2) = d.overflowing_sub(ba as BigDigit);
+        ba = b1 || b2;
+        *ai = d;
+
+        let qa = x * DoubleBigDigit::from(cs.u1) + cb_a;
+        let qb = y * DoubleBigDigit::from(cs.v1) + cb_b;
+        cb_a = qa >> big_digit::BITS;
+        cb_b = qb >> big_digit::BITS;
+        let (pos, neg) = if cs.even { (qb, qa) } else { (qa, qb) };
+        let (d, b1) = (pos as BigDigit).overflowing_sub(neg as BigDigit);
+        let (d, b2) = d.overflowing_sub(bb as BigDigit);
+        bb = b1 || b2;
+        *bi = d;
+    }
+    debug_assert!(if cs.even {
+        ca_a == ca_b + ba as DoubleBigDigit && cb_b == cb_a + bb as DoubleBigDigit
+    } else {
+        ca_b == ca_a + ba as DoubleBigDigit && cb_a == cb_b + bb as DoubleBigDigit
+    });
+
+    a.normalize();
+    b.normalize();
+}
+
+/// Applies a simulated cosequence to a pair of Bézout coefficients, like `lehmer_update`.
+fn lehmer_update_cofactors(a: &mut BigInt, b: &mut BigInt, cs: &Cosequence) {
+    let (x, y) = if cs.even {
+        (&*a * cs.u0 - &*b * cs.v0, &*b * cs.v1 - &*a * cs.u1)
+    } else {
+        (&*b * cs.v0 - &*a * cs.u0, &*a * cs.u1 - &*b * cs.v1)
+    };
+    *a = x;
+    *b = y;
+}
+
+/// A single multiprecision Euclidean step, used when no quotient could be simulated.
+fn euclid_update(a: &mut BigUint, b: &mut BigUint, cofactors: &mut Option<(BigInt, BigInt)>) {
+    let (q, r) = a.div_rem(b);
+    *a = mem::replace(b, r);
+    if let Some((ref mut ua, ref mut ub)) = *cofactors {
+        let t = &*ua - &*ub * BigInt::from(q);
+        *ua = mem::replace(ub, t);
+    }
+}
+
+/// Lehmer's gcd algorithm, returning `gcd(a, b)` and, if `extended`, the
+/// Bézout coefficient `x` with `a * x ≡ gcd(a, b) (mod b)`.
+///
+/// The coefficient is the same one the plain extended Euclidean algorithm finds,
+/// since each simulated run of steps reproduces exactly the same quotients.
+pub(super) fn lehmer_gcd(
+    mut a: BigUint,
+    mut b: BigUint,
+    extended: bool,
+) -> (BigUint, Option<BigInt>) {
+    // `ua` (`ub`) tracks how many times the input `a` has been accumulated into `a` (`b`).
+    let mut cofactors = if extended {
+        Some((BigInt::one(), BigInt::zero()))
+    } else {
+        None
+    };
+
+    if a < b {
+        mem::swap(&mut a, &mut b);
+        if let Some((ref mut ua, ref mut ub)) = cofactors {
+            mem::swap(ua, ub);
+        }
+    }
+
+    // loop invariant: a >= b
+    while b.data.len() > 1 {
+        let cs = lehmer_simulate(&a.data, &b.data, 0);
+        if cs.v0 != 0 {
+            lehmer_update(&mut a, &mut b, &cs);
+            if let Some((ref mut ua, ref mut ub)) = cofactors {
+                lehmer_update_cofactors(ua, ub, &cs);
+            }
+        } else {
+            euclid_update(&mut a, &mut b, &mut cofactors);
+        }
+    }
+
+    if !b.is_zero() {
+        if a.data.len() > 1 {
+            euclid_update(&mut a, &mut b, &mut cofactors);
+        }
+        if !b.is_zero() {
+            // Both values fit in a single digit now.
+            let (mut x, mut y) = (a.data[0], b.data[0]);
+            let (mut ua, mut ub): (BigDigit, BigDigit) = (1, 0);
+            let (mut va, mut vb): (BigDigit, BigDigit) = (0, 1);
+            let mut even = true;
+            while y != 0 {
+                let (q, r) = (x / y, x % y);
+                x = mem::replace(&mut y, r);
+                let u = ua + q * ub;
+                ua = mem::replace(&mut ub, u);
+                let v = va + q * vb;
+                va = mem::replace(&mut vb, v);
+                even = !even;
+            }
+            if let Some((ref mut cua, ref cub)) = cofactors {
+                let (s, t) = (&*cua * ua, cub * va);
+                *cua = if even { s - t } else { t - s };
+            }
+            a = BigUint::from(x);
+        }
+    }
+
+    (a, cofactors.map(|(ua, _)| ua))
+}
+
+/// Returns `(g, x, y)` with `g = gcd(a, b) >= 0` and `a * x + b * y = g`.
+pub(crate) fn extended_gcd(a: &BigInt, b: &BigInt) -> (BigInt, BigInt, BigInt) {
+    let (g, x) = lehmer_gcd(a.magnitude().clone(), b.magnitude().clone(), true);
+    let mut x = x.unwrap();
+    if a.is_negative() {
+        x = -x;
+    }
+    let g = BigInt::from(g);
+    let y = if b.is_zero() {
+        BigInt::zero()
+    } else {
+        (&g - a * &x) / b
+    };
+    (g, x, y)
+}
+
+/// Returns the number of trailing zero bits of `x`, or zero for zero.
+#[inline]
+pub(super) fn twos(x: &BigUint) -> u64 {
+    x.trailing_zeros().unwrap_or(0)
+}
+
+/// Computes the Jacobi symbol `(a / n)` for an odd `n` with the binary algorithm,
+/// taking out factors of two like Stein's gcd, and using quadratic reciprocity
+/// to swap the operands while keeping the larger one first.
+pub(crate) fn jacobi(a: &BigUint, n: &BigUint) -> i8 {
+    assert!(n.is_odd(), "the Jacobi symbol requires an odd modulus");
+    let mut a = if a >= n { a % n } else { a.clone() };
+    let mut n = n.clone();
+    let mut j = 1;
+    while !a.is_zero() {
+        // (2 / n) = -1 if and only if n = 3 or 5 (mod 8)
+        let z = twos(&a);
+        a >>= z;
+        let n8 = n.data[0] & 7;
+        if z & 1 == 1 && (n8 == 3 || n8 == 5) {
+            j = -j;
+        }
+
+        // (a / n) = -(n / a) if and only if a = n = 3 (mod 4)
+        if a < n {
+            mem::swap(&mut a, &mut n);
+            if a.data[0] & 3 == 3 && n.data[0] & 3 == 3 {
+                j = -j;
+            }
+        }
+
+        // (a / n) = ((a - n) / n), or reduce all at once if `a` is much larger
+        if a.data.len() > n.data.len() {
+            a %= &n;
+        } else {
+            a -= &n;
+        }
+    }
+    if n.is_one() {
+        j
+    } else {
+        0
+    }
+}
+
+pub(super) fn modinv(a: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+    assert!(
+        !modulus.is_zero(),
+        "attempt to calculate with zero modulus!"
+    );
+    if modulus.is_one() {
+        return Some(BigUint::zero());
+    }
+
+    let (g, x) = lehmer_gcd(a % modulus, modulus.clone(), true);
+    if !g.is_one() {
+        return None;
+    }
+
+    // The Euclidean cofactor is bounded by `modulus / 2`, so one correction suffices.
+    let x = x.unwrap();
+    debug_assert!(x.magnitude() < modulus);
+    if x.is_negative() {
+        Some(modulus - x.magnitude())
+    } else {
+        Some(x.magnitude().clone())
+    }
+}
+
+/// A matrix `M` with non-negative entries and determinant 1, relating a pair
+/// of inputs `(A, B)` to the current pair `(a, b)` as `(A; B) = M (a; b)`.
+struct Matrix {
+    m00: BigUint,
+    m01: BigUint,
+    m10: BigUint,
+    m11: BigUint,
+}
+
+impl Matrix {
+    fn identity() -> Self {
+        Matrix {
+            m00: BigUint::one(),
+            m01: BigUint::zero(),
+            m10: BigUint::zero(),
+            m11: BigUint::one(),
+        }
+    }
+
+    /// Returns `self * other`.
+    fn mul(&self, other: &Matrix) -> Matrix {
+        Matrix {
+            m00: &self.m00 * &other.m00 + &self.m01 * &other.m10,
+            m01: &self.m00 * &other.m01 + &self.m01 * &other.m11,
+            m10: &self.m10 * &other.m00 + &self.m11 * &other.m10,
+            m11: &self.m10 * &other.m01 + &self.m11 * &other.m11,
+        }
+    }
+
+    /// Multiplies by `(s00, s01; s10, s11)` on the right, for single-digit entries.
+    fn mul_digits(&mut self, s: [BigDigit; 4]) {
+        combine(&mut self.m00, &mut self.m01, [s[0], s[2], s[1], s[3]]);
+        combine(&mut self.m10, &mut self.m11, [s[0], s[2], s[1], s[3]]);
+    }
+
+    /// Records that `a` was reduced by `q * b`, multiplying by `(1, q; 0, 1)`.
+    fn reduced_a(&mut self, q: &BigUint) {
+        match q.data[..] {
+            [q] => self.mul_digits([1, q, 0, 1]),
+            _ => {
+                self.m01 += q * &self.m00;
+                self.m11 += q * &self.m10;
+            }
+        }
+    }
+
+    /// Records that `b` was reduced by `q * a`, multiplying by `(1, 0; q, 1)`.
+    fn reduced_b(&mut self, q: &BigUint) {
+        match q.data[..] {
+            [q] => self.mul_digits([1, 0, q, 1]),
+            _ => {
+                self.m00 += q * &self.m01;
+                self.m10 += q * &self.m11;
+            }
+        }
+    }
+}
+
+/// Sets `(x, y) = (c00 * x + c01 * y, c10 * x + c11 * y)` in place,
+/// for single-digit coefficients `c = [c00, c01, c10, c11]`.
+fn combine(x: &mut BigUint, y: &mut BigUint, c: [BigDigit; 4]) {
+    let n = cmp::max(x.data.len(), y.data.len());
+    x.data.resize(n, 0);
+    y.data.resize(n, 0);
+
+    // Each sum of two products needs a two-digit carry, kept as two carry digits.
+    let c = [
+        DoubleBigDigit::from(c[0]),
+        DoubleBigDigit::from(c[1]),
+        DoubleBigDigit::from(c[2]),
+        DoubleBigDigit::from(c[3]),
+    ];
+    let (mut cx0, mut cx1, mut cy0, mut cy1) = (0, 0, 0, 0);
+    for (xi, yi) in x.data.iter_mut().zip(y.data.iter_mut()) {
+        let (a, b) = (DoubleBigDigit::from(*xi), DoubleBigDigit::from(*yi));
+
+        let p = c[0] * a + cx0;
+        let q = c[1] * b + DoubleBigDigit::from(p as BigDigit) + cx1;
+        cx0 = p >> big_digit::BITS;
+        cx1 = q >> big_digit::BITS;
+        *xi = q as BigDigit;
+
+        let p = c[2] * a + cy0;
+        let q = c[3] * b + DoubleBigDigit::from(p as BigDigit) + cy1;
+        cy0 = p >> big_digit::BITS;
+        cy1 = q >> big_digit::BITS;
+        *yi = q as BigDigit;
+    }
+
+    for &mut (ref mut z, carry) in &mut [(x, cx0 + cx1), (y, cy0 + cy1)] {
+        if carry != 0 {
+            let (hi, lo) = big_digit::from_doublebigdigit(carry);
+            z.data.push(lo);
+            z.data.push(hi);
+        }
+        z.normalize();
+    }
+}
+
+/// Returns the matrix `S` with `(x; y) = S (x'; y')`, where `x'` and `y'` are the
+/// results of `lehmer_update` on `x` and `y`, traded when the run of steps is odd,
+/// so that the determinant is 1.
+fn cosequence_inverse(cs: &Cosequence) -> [BigDigit; 4] {
+    if cs.even {
+        [cs.v1, cs.v0, cs.u1, cs.u0]
+    } else {
+        [cs.v0, cs.v1, cs.u0, cs.u1]
+    }
+}
+
+/// One reduction step of the half-GCD, keeping both `a` and `b` longer than `s` digits.
+/// Returns `false` if no such reduction is possible.
+fn hgcd_step(a: &mut BigUint, b: &mut BigUint, s: usize, m: &mut Matrix) -> bool {
+    let a_is_larger = *a >= *b;
+    let (x, y) = if a_is_larger { (a, b) } else { (b, a) };
+
+    // Try a run of Euclidean steps simulated on the leading digits, as long as
+    // the bound of `s` digits is within reach of the leading digit.
+    let scale = x.bits().saturating_sub(u64::from(big_digit::BITS));
+    let limit = (s as u64) * u64::from(big_digit::BITS);
+    if y.data.len() >= 2 && limit < scale + u64::from(big_digit::BITS) {
+        let min = 1 << limit.saturating_sub(scale);
+        let cs = lehmer_simulate(&x.data, &y.data, min);
+        if cs.v0 != 0 {
+            lehmer_update(x, y, &cs);
+            if !cs.even {
+                mem::swap(x, y);
+            }
+            let inv = cosequence_inverse(&cs);
+            if x.data.len() > s && y.data.len() > s {
+                if a_is_larger {
+                    m.mul_digits(inv);
+                } else {
+                    // `a` and `b` have traded roles, so conjugate by the permutation
+                    m.mul_digits([inv[3], inv[2], inv[1], inv[0]]);
+                }
+                return true;
+            }
+            // went too far, so undo it
+            combine(x, y, inv);
+        }
+    }
+
+    // Otherwise reduce the larger value by as many multiples of the smaller
+    // as possible, while it stays longer than `s` digits.
+    let (mut q, mut r) = x.div_rem(y);
+    if r.data.len() <= s {
+        if q.is_one() {
+            return false;
+        }
+        q -= 1u32;
+        r += &*y;
+    }
+    *x = r;
+    if a_is_larger {
+        m.reduced_a(&q);
+    } else {
+        m.reduced_b(&q);
+    }
+    true
+}
+
+/// Returns `(x >> (p * BITS), x mod 2^(p * BITS))`.
+fn split_at_digit(x: &BigUint, p: usize) -> (BigUint, BigUint) {
+    if x.data.len() <= p {
+        (BigUint::zero(), x.clone())
+    } else {
+        let (lo, hi) = x.data.split_at(p);
+        (biguint_from_vec(hi.to_vec()), biguint_from_vec(lo.to_vec()))
+    }
+}
+
+/// Runs the half-GCD on the digits of `a` and `b` above `p`, and applies the
+/// resulting matrix to the full values. Returns `None` if no reduction was possible.
+fn hgcd_reduce(a: &mut BigUint, b: &mut BigUint, p: usize) -> Option<Matrix> {
+    let (mut ah, al) = split_at_digit(a, p);
+    let (mut bh, bl) = split_at_digit(b, p);
+    let m = hgcd(&mut ah, &mut bh)?;
+
+    // (a; b) = M^-1 (ah; bh) * 2^(p * BITS) + M^-1 (al; bl). The low part should
+    // be too small to make either result non-positive, since `ah` and `bh` were
+    // kept large, but if it isn't, skip this reduction and leave `a` and `b` as
+    // they were.
+    let shift = p * big_digit::BITS as usize;
+    let a_pos = (ah << shift) + &m.m11 * &al;
+    let a_neg = &m.m01 * &bl;
+    let b_pos = (bh << shift) + &m.m00 * &bl;
+    let b_neg = &m.m10 * &al;
+    if a_pos <= a_neg || b_pos <= b_neg {
+        return None;
+    }
+    *a = a_pos - a_neg;
+    *b = b_pos - b_neg;
+    Some(m)
+}
+
+/// The half-GCD: reduces `a` and `b`, of at most `n` digits, while both stay longer
+/// than `s = n / 2 + 1` digits, and returns the matrix `M` of the reduction,
+/// whose entries are about `n / 2` digits. Returns `None` if no reduction was possible.
+///
+/// This follows Möller, "On Schönhage's algorithm and subquadratic integer gcd
+/// computation", Mathematics of Computation 77 (2008).
+fn hgcd(a: &mut BigUint, b: &mut BigUint) -> Option<Matrix> {
+    let mut n = cmp::max(a.data.len(), b.data.len());
+    let s = n / 2 + 1;
+    if cmp::min(a.data.len(), b.data.len()) <= s {
+        return None;
+    }
+
+    let mut m = Matrix::identity();
+    let mut success = false;
+
+    if n >= HGCD_THRESHOLD {
+        // Reduce the top half recursively, to about 3/4 of the original size.
+        if let Some(m1) = hgcd_reduce(a, b, n / 2) {
+            m = m1;
+            success = true;
+        }
+        let n2 = 3 * n / 4 + 1;
+        while cmp::max(a.data.len(), b.data.len()) > n2 {
+            if !hgcd_step(a, b, s, &mut m) {
+                return if success { Some(m) } else { None };
+            }
+            success = true;
+        }
+
+        // Then reduce the top of what remains down to `s` digits.
+        n = cmp::max(a.data.len(), b.data.len());
+        if n > s + 2 {
+            if let Some(m2) = hgcd_reduce(a, b, 2 * s - n + 1) {
+                m = m.mul(&m2);
+                success = true;
+            }
+        }
+    }
+
+    while hgcd_step(a, b, s, &mut m) {
+        success = true;
+    }
+    if success {
+        Some(m)
+    } else {
+        None
+    }
+}
+
+/// Computes the gcd, using the half-GCD on the top half of the digits to remove
+/// about a quarter of them at a time, and Lehmer's algorithm once they're smaller.
+pub(super) fn gcd(a: &BigUint, b: &BigUint) -> BigUint {
+    let mut a = a.clone();
+    let mut b = b.clone();
+    loop {
+        if a < b {
+            mem::swap(&mut a, &mut b);
+        }
+        if b.data.len() < HGCD_THRESHOLD {
+            return lehmer_gcd(a, b, false).0;
+        }
+        if a.data.len() > b.data.len() {
+            a %= &b;
+            continue;
+        }
+
+        let p = a.data.len() / 2;
+        if hgcd_reduce(&mut a, &mut b, p).is_none() {
+            a %= &b;
+        }
+    }
+}
+
+#[test]
+fn test_jacobi() {
+    // (a / p) agrees with Euler's criterion for odd primes
+    for &p in &[3u32, 5, 7, 11, 13, 97, 65537, 2147483647] {
+        let p = BigUint::from(p);
+        let half = (&p - 1u32) >> 1;
+        for a in 0u32..200 {
+            let a = BigUint::from(a);
+            let euler = a.modpow(&half, &p);
+            let expected = if euler.is_zero() {
+                0
+            } else if euler.is_one() {
+                1
+            } else {
+                -1
+            };
+            assert_eq!(jacobi(&a, &p), expected);
+            assert_eq!(jacobi(&(&a + &p * 1000u32), &p), expected);
+        }
+    }
+
+    // (a / 15) = (a / 3) (a / 5)
+    let n = BigUint::from(15u32);
+    let symbols = [0, 1, 1, 0, 1, 0, 0, -1, 1, 0, 0, -1, 0, -1, -1];
+    for (a, &j) in symbols.iter().enumerate() {
+        assert_eq!(jacobi(&BigUint::from(a), &n), j);
+    }
+    assert_eq!(jacobi(&BigUint::zero(), &BigUint::one()), 1);
+}
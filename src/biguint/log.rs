@@ -0,0 +1,71 @@
+use super::BigUint;
+
+use num_traits::{Pow, ToPrimitive, Zero};
+
+/// The number of fractional bits in the fixed-point logarithms.
+const FRAC_BITS: u32 = 56;
+
+/// Returns an estimate of `log2(x) * 2^FRAC_BITS` for a non-zero `x`, from its top
+/// 64 bits. The fractional part is found bit by bit, by repeatedly squaring the
+/// mantissa in fixed point, so this doesn't need `std` for floating point `log2`.
+fn log2_fixed(x: &BigUint) -> u128 {
+    let bits = x.bits();
+    let mut t = if bits > 64 {
+        (x >> (bits - 64)).to_u64().unwrap()
+    } else {
+        x.to_u64().unwrap() << (64 - bits)
+    };
+
+    // t / 2^63 is the mantissa in [1, 2)
+    let mut frac = 0u128;
+    for _ in 0..FRAC_BITS {
+        let square = u128::from(t) * u128::from(t);
+        frac <<= 1;
+        if square >> 127 == 1 {
+            frac |= 1;
+            t = (square >> 64) as u64;
+        } else {
+            t = (square >> 63) as u64;
+        }
+    }
+    (u128::from(bits - 1) << FRAC_BITS) | frac
+}
+
+pub(super) fn checked_ilog(x: &BigUint, base: &BigUint) -> Option<u64> {
+    if x.is_zero() || base.bits() < 2 {
+        return None;
+    }
+    if x < base {
+        return Some(0);
+    }
+    if base.count_ones() == 1 {
+        return Some((x.bits() - 1) / (base.bits() - 1));
+    }
+
+    // Estimate from the logarithms, then correct against the actual powers.
+    let mut e = (log2_fixed(x) / log2_fixed(base)) as u64;
+    let mut power = Pow::pow(base, e);
+    while power > *x {
+        power /= base;
+        e -= 1;
+    }
+    loop {
+        power *= base;
+        if power > *x {
+            return Some(e);
+        }
+        e += 1;
+    }
+}
+
+#[test]
+fn test_log2_fixed() {
+    let one = 1u128 << FRAC_BITS;
+    assert_eq!(log2_fixed(&BigUint::from(1u8)), 0);
+    assert_eq!(log2_fixed(&(BigUint::from(1u8) << 1000u32)), 1000 * one);
+
+    // log2(3) = 1.5849625007211562...
+    let log3 = log2_fixed(&BigUint::from(3u32));
+    let expected = (1.584_962_500_721_156_2 * one as f64) as u128;
+    assert!(log3 <= expected + 16 && expected <= log3 + 16);
+}
@@ -0,0 +1,128 @@
+use super::BigUint;
+
+use num_traits::{One, Zero};
+
+/// Returns `(F(n), F(n - 1))` for `n >= 1`, by fast doubling with the identities
+///
+/// - `F(2k + 1) = 4 F(k)^2 - F(k - 1)^2 + 2 (-1)^k`
+/// - `F(2k - 1) = F(k)^2 + F(k - 1)^2`
+/// - `F(2k) = F(2k + 1) - F(2k - 1)`
+///
+/// which take just two squarings for each bit of `n`.
+fn fibonacci_down(n: u64) -> (BigUint, BigUint) {
+    debug_assert!(n >= 1);
+    let mut f = BigUint::one();
+    let mut f_prev = BigUint::zero();
+    let mut k_odd = true;
+    for i in (0..63 - n.leading_zeros()).rev() {
+        let f2 = f.square();
+        let f_prev2 = f_prev.square();
+        let f_next = if k_odd {
+            (f2.clone() << 2u8) - f_prev2.clone() - 2u32
+        } else {
+            (f2.clone() << 2u8) + 2u32 - f_prev2.clone()
+        };
+        let f_down = f2 + f_prev2;
+        let f_mid = &f_next - &f_down;
+
+        k_odd = (n >> i) & 1 == 1;
+        if k_odd {
+            f = f_next;
+            f_prev = f_mid;
+        } else {
+            f = f_mid;
+            f_prev = f_down;
+        }
+    }
+    (f, f_prev)
+}
+
+pub(super) fn fibonacci(n: u64) -> BigUint {
+    if n == 0 {
+        return BigUint::zero();
+    }
+    fibonacci_down(n).0
+}
+
+pub(super) fn fibonacci_pair(n: u64) -> (BigUint, BigUint) {
+    let next = n.checked_add(1).expect("memory overflow");
+    let (f_next, f) = fibonacci_down(next);
+    (f, f_next)
+}
+
+pub(super) fn lucas(n: u64) -> BigUint {
+    if n == 0 {
+        return BigUint::from(2u32);
+    }
+    // L(n) = F(n + 1) + F(n - 1) = F(n) + 2 F(n - 1)
+    let (f, f_prev) = fibonacci_down(n);
+    f + (f_prev << 1u8)
+}
+
+/// Returns `(x - y) mod m`, for `x` and `y` already reduced modulo `m`.
+fn sub_mod(x: BigUint, y: &BigUint, m: &BigUint) -> BigUint {
+    if x >= *y {
+        x - y
+    } else {
+        x + m - y
+    }
+}
+
+/// Returns `(U(n), V(n))` of the Lucas sequences with parameters `p` and `q`,
+/// modulo `m`.
+///
+/// This works from `(U(k), U(k + 1))` with the identities
+///
+/// - `U(2k) = U(k) (2 U(k + 1) - p U(k))`
+/// - `U(2k + 1) = U(k + 1)^2 - q U(k)^2`
+///
+/// which don't need any division, so `m` can be even, and finally takes
+/// `V(n) = 2 U(n + 1) - p U(n)`.
+pub(super) fn lucas_sequence(
+    p: &BigUint,
+    q: &BigUint,
+    n: &BigUint,
+    m: &BigUint,
+) -> (BigUint, BigUint) {
+    assert!(!m.is_zero(), "attempt to calculate with zero modulus!");
+    let p = p % m;
+    let q = q % m;
+
+    let mut u = BigUint::zero();
+    let mut u_next = BigUint::one() % m;
+    for i in (0..n.bits()).rev() {
+        let uu = u.square() % m;
+        let u_double = sub_mod(((&u * &u_next) << 1u8) % m, &(&p * &uu % m), m);
+        let u_double_next = sub_mod(u_next.square() % m, &(&q * uu % m), m);
+        if n.bit(i) {
+            u_next = sub_mod(&p * &u_double_next % m, &(&q * u_double % m), m);
+            u = u_double_next;
+        } else {
+            u = u_double;
+            u_next = u_double_next;
+        }
+    }
+
+    let v = sub_mod((u_next << 1u8) % m, &(&p * &u % m), m);
+    (u, v)
+}
+
+#[test]
+fn test_fibonacci_small() {
+    let (mut a, mut b) = (0u64, 1u64);
+    for n in 0..90 {
+        assert_eq!(fibonacci(n), BigUint::from(a));
+        assert_eq!(fibonacci_pair(n), (BigUint::from(a), BigUint::from(b)));
+        let next = a + b;
+        a = b;
+        b = next;
+    }
+
+    let (mut a, mut b) = (2u64, 1u64);
+    for n in 0..90 {
+        assert_eq!(lucas(n), BigUint::from(a));
+        let next = a + b;
+        a = b;
+        b = next;
+    }
+}
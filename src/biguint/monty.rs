@@ -1,18 +1,15 @@
-use crate::std_alloc::Vec;
-use core::mem;
 use core::ops::Shl;
+use num_integer::Integer;
 use num_traits::{One, Zero};
 
 use crate::big_digit::{self, BigDigit, DoubleBigDigit, SignedDoubleBigDigit};
 use crate::biguint::BigUint;
 
-struct MontyReducer {
-    n0inv: BigDigit,
-}
+use super::power::sliding_window_pow;
 
 // k0 = -m**-1 mod 2**BITS. Algorithm from: Dumas, J.G. "On Newton–Raphson
 // Iteration for Multiplicative Inverses Modulo Prime Powers".
-fn inv_mod_alt(b: BigDigit) -> BigDigit {
+pub(super) fn inv_mod_alt(b: BigDigit) -> BigDigit {
     assert_ne!(b & 1, 0);
 
     let mut k0 = 2 - b as SignedDoubleBigDigit;
@@ -27,13 +24,6 @@ fn inv_mod_alt(b: BigDigit) -> BigDigit {
     -k0 as BigDigit
 }
 
-impl MontyReducer {
-    fn new(n: &BigUint) -> Self {
-        let n0inv = inv_mod_alt(n.data[0]);
-        MontyReducer { n0inv }
-    }
-}
-
 /// Computes z mod m = x * y * 2 ** (-n*_W) mod m
 /// assuming k = -1/m mod 2**_W
 /// See Gueron, "Efficient Software Implementations of Modular Exponentiation".
@@ -87,6 +77,49 @@ fn montgomery(x: &BigUint, y: &BigUint, m: &BigUint, k: BigDigit, n: usize) -> B
     z
 }
 
+/// Computes z mod m = x * x * 2 ** (-n*_W) mod m, like `montgomery(x, x, m, k, n)`,
+/// but squaring first and then doing a separate Montgomery reduction, which
+/// saves about a quarter of the digit multiplications.
+#[allow(clippy::many_single_char_names)]
+fn montgomery_square(x: &BigUint, m: &BigUint, k: BigDigit, n: usize) -> BigUint {
+    assert!(
+        x.data.len() == n && m.data.len() == n,
+        "{:?} {:?} {}",
+        x,
+        m,
+        n
+    );
+
+    let mut z = x.square();
+    z.data.resize(n * 2, 0);
+
+    let mut c: BigDigit = 0;
+    for i in 0..n {
+        let t = z.data[i].wrapping_mul(k);
+        let c2 = add_mul_vvw(&mut z.data[i..n + i], &m.data, t);
+        let cx = z.data[n + i].wrapping_add(c);
+        let cy = cx.wrapping_add(c2);
+        z.data[n + i] = cy;
+        if cx < c || cy < c2 {
+            c = 1;
+        } else {
+            c = 0;
+        }
+    }
+
+    if c == 0 {
+        z.data = z.data[n..].to_vec();
+    } else {
+        {
+            let (first, second) = z.data.split_at_mut(n);
+            sub_vv(first, second, &m.data);
+        }
+        z.data = z.data[..n].to_vec();
+    }
+
+    z
+}
+
 #[inline(always)]
 fn add_mul_vvw(z: &mut [BigDigit], x: &[BigDigit], y: BigDigit) -> BigDigit {
     let mut c = 0;
@@ -131,95 +164,188 @@ fn mul_add_www(x: BigDigit, y: BigDigit, c: BigDigit) -> (BigDigit, BigDigit) {
     ((z >> big_digit::BITS) as BigDigit, z as BigDigit)
 }
 
-/// Calculates x ** y mod m using a fixed, 4-bit window.
-#[allow(clippy::many_single_char_names)]
-pub(super) fn monty_modpow(x: &BigUint, y: &BigUint, m: &BigUint) -> BigUint {
-    assert!(m.data[0] & 1 == 1);
-    let mr = MontyReducer::new(m);
-    let num_words = m.data.len();
+/// Precomputed data for Montgomery multiplication modulo a fixed odd modulus,
+/// to make many modular multiplications and exponentiations cheaper.
+///
+/// Values are converted to Montgomery form with [`to_monty`](#method.to_monty),
+/// operated on as [`MontgomeryElement`]s, and converted back with
+/// [`from_monty`](#method.from_monty).
+///
+/// [`MontgomeryElement`]: struct.MontgomeryElement.html
+#[derive(Clone, Debug)]
+pub struct MontgomeryContext {
+    modulus: BigUint,
+    /// -m**-1 mod 2**BITS
+    n0inv: BigDigit,
+    /// 2**(2*_W*len(m)) mod m, with equal length to that of m
+    rr: BigUint,
+}
+
+/// A value `x * 2**(_W*len(m)) mod m` in Montgomery form, for use with the
+/// `MontgomeryContext` of modulus `m` that created it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MontgomeryElement {
+    value: BigUint,
+}
 
-    let mut x = x.clone();
+impl MontgomeryContext {
+    /// Creates a context for the given modulus.
+    ///
+    /// Panics if the modulus is even, including zero.
+    pub fn new(modulus: &BigUint) -> Self {
+        assert!(
+            modulus.is_odd(),
+            "Montgomery multiplication requires an odd modulus!"
+        );
+        let num_words = modulus.data.len();
+        let n0inv = inv_mod_alt(modulus.data[0]);
 
-    // We want the lengths of x and m to be equal.
-    // It is OK if x >= m as long as len(x) == len(m).
-    if x.data.len() > num_words {
-        x %= m;
-        // Note: now len(x) <= numWords, not guaranteed ==.
+        let mut rr = BigUint::one();
+        rr = (rr.shl(2 * num_words as u64 * u64::from(big_digit::BITS))) % modulus;
+        rr.data.resize(num_words, 0);
+
+        MontgomeryContext {
+            modulus: modulus.clone(),
+            n0inv,
+            rr,
+        }
     }
-    if x.data.len() < num_words {
-        x.data.resize(num_words, 0);
+
+    /// Returns the modulus of this context.
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
     }
 
-    // rr = 2**(2*_W*len(m)) mod m
-    let mut rr = BigUint::one();
-    rr = (rr.shl(2 * num_words as u64 * u64::from(big_digit::BITS))) % m;
-    if rr.data.len() < num_words {
-        rr.data.resize(num_words, 0);
+    /// Returns `x` with equal length to that of the modulus.
+    fn padded(&self, x: &BigUint) -> BigUint {
+        let mut x = x.clone();
+        x.data.resize(self.modulus.data.len(), 0);
+        x
     }
-    // one = 1, with equal length to that of m
-    let mut one = BigUint::one();
-    one.data.resize(num_words, 0);
-
-    let n = 4;
-    // powers[i] contains x^i
-    let mut powers = Vec::with_capacity(1 << n);
-    powers.push(montgomery(&one, &rr, m, mr.n0inv, num_words));
-    powers.push(montgomery(&x, &rr, m, mr.n0inv, num_words));
-    for i in 2..1 << n {
-        let r = montgomery(&powers[i - 1], &powers[1], m, mr.n0inv, num_words);
-        powers.push(r);
-    }
-
-    // initialize z = 1 (Montgomery 1)
-    let mut z = powers[0].clone();
-    z.data.resize(num_words, 0);
-    let mut zz = BigUint::zero();
-    zz.data.resize(num_words, 0);
-
-    // same windowed exponent, but with Montgomery multiplications
-    for i in (0..y.data.len()).rev() {
-        let mut yi = y.data[i];
-        let mut j = 0;
-        while j < big_digit::BITS {
-            if i != y.data.len() - 1 || j != 0 {
-                zz = montgomery(&z, &z, m, mr.n0inv, num_words);
-                z = montgomery(&zz, &zz, m, mr.n0inv, num_words);
-                zz = montgomery(&z, &z, m, mr.n0inv, num_words);
-                z = montgomery(&zz, &zz, m, mr.n0inv, num_words);
+
+    fn montgomery(&self, x: &BigUint, y: &BigUint) -> BigUint {
+        montgomery(x, y, &self.modulus, self.n0inv, self.modulus.data.len())
+    }
+
+    fn montgomery_square(&self, x: &BigUint) -> BigUint {
+        montgomery_square(x, &self.modulus, self.n0inv, self.modulus.data.len())
+    }
+
+    /// Fully reduces a result of `montgomery`, which may not be less than `m`.
+    fn reduce(&self, mut z: BigUint) -> BigUint {
+        z.normalize();
+        // One last reduction, just in case.
+        // See golang.org/issue/13907.
+        if z >= self.modulus {
+            // Common case is m has high bit set; in that case,
+            // since z is the same length as m, there can be just
+            // one multiple of m to remove. Just subtract.
+            // We think that the subtract should be sufficient in general,
+            // so do that unconditionally, but double-check,
+            // in case our beliefs are wrong.
+            // The div is not expected to be reached.
+            z -= &self.modulus;
+            if z >= self.modulus {
+                z %= &self.modulus;
             }
-            zz = montgomery(
-                &z,
-                &powers[(yi >> (big_digit::BITS - n)) as usize],
-                m,
-                mr.n0inv,
-                num_words,
-            );
-            mem::swap(&mut z, &mut zz);
-            yi <<= n;
-            j += n;
         }
+        z
+    }
+
+    /// Returns the Montgomery form of `1`.
+    pub fn one(&self) -> MontgomeryElement {
+        self.to_monty(&BigUint::one())
+    }
+
+    /// Converts `x` to Montgomery form, reducing it modulo `m` first if needed.
+    pub fn to_monty(&self, x: &BigUint) -> MontgomeryElement {
+        let x = if *x >= self.modulus {
+            self.padded(&(x % &self.modulus))
+        } else {
+            self.padded(x)
+        };
+        MontgomeryElement {
+            value: self.reduce(self.montgomery(&x, &self.rr)),
+        }
+    }
+
+    /// Converts `x` from Montgomery form back to a regular number in `[0, m)`.
+    pub fn from_monty(&self, x: &MontgomeryElement) -> BigUint {
+        let mut one = BigUint::one();
+        one.data.resize(self.modulus.data.len(), 0);
+        self.reduce(self.montgomery(&self.padded(&x.value), &one))
+    }
+
+    /// Returns the Montgomery form of the modular product of `x` and `y`.
+    pub fn mul(&self, x: &MontgomeryElement, y: &MontgomeryElement) -> MontgomeryElement {
+        let z = self.montgomery(&self.padded(&x.value), &self.padded(&y.value));
+        MontgomeryElement {
+            value: self.reduce(z),
+        }
+    }
+
+    /// Returns the Montgomery form of the modular square of `x`.
+    pub fn square(&self, x: &MontgomeryElement) -> MontgomeryElement {
+        let z = self.montgomery_square(&self.padded(&x.value));
+        MontgomeryElement {
+            value: self.reduce(z),
+        }
+    }
+
+    /// Returns the Montgomery form of the modular sum of `x` and `y`.
+    pub(super) fn add(&self, x: &MontgomeryElement, y: &MontgomeryElement) -> MontgomeryElement {
+        let mut z = &x.value + &y.value;
+        if z >= self.modulus {
+            z -= &self.modulus;
+        }
+        MontgomeryElement { value: z }
+    }
+
+    /// Returns the Montgomery form of the modular difference of `x` and `y`.
+    pub(super) fn sub(&self, x: &MontgomeryElement, y: &MontgomeryElement) -> MontgomeryElement {
+        let value = if x.value >= y.value {
+            &x.value - &y.value
+        } else {
+            &x.value + &self.modulus - &y.value
+        };
+        MontgomeryElement { value }
+    }
+
+    /// Returns the Montgomery form of `x ** exponent mod m`.
+    pub fn pow(&self, x: &MontgomeryElement, exponent: &BigUint) -> MontgomeryElement {
+        MontgomeryElement {
+            value: self.reduce(self.pow_padded(self.padded(&x.value), exponent)),
+        }
+    }
+
+    /// Returns `x ** exponent mod m` for a regular number `x`, like `BigUint::modpow`.
+    pub fn modpow(&self, x: &BigUint, exponent: &BigUint) -> BigUint {
+        let x = self.to_monty(x);
+        self.from_monty(&self.pow(&x, exponent))
     }
 
-    // convert to regular number
-    zz = montgomery(&z, &one, m, mr.n0inv, num_words);
-
-    zz.normalize();
-    // One last reduction, just in case.
-    // See golang.org/issue/13907.
-    if zz >= *m {
-        // Common case is m has high bit set; in that case,
-        // since zz is the same length as m, there can be just
-        // one multiple of m to remove. Just subtract.
-        // We think that the subtract should be sufficient in general,
-        // so do that unconditionally, but double-check,
-        // in case our beliefs are wrong.
-        // The div is not expected to be reached.
-        zz -= m;
-        if zz >= *m {
-            zz %= m;
+    /// Calculates x ** y in Montgomery form using a sliding window,
+    /// where `x` has equal length to that of `m`.
+    fn pow_padded(&self, x: BigUint, y: &BigUint) -> BigUint {
+        if y.is_zero() {
+            return self.padded(&self.one().value);
         }
+        sliding_window_pow(
+            x,
+            &y.data,
+            |a, b| self.montgomery(a, b),
+            |a| self.montgomery_square(a),
+        )
     }
+}
 
-    zz.normalize();
-    zz
+impl MontgomeryElement {
+    pub(super) fn is_zero(&self) -> bool {
+        self.value.is_zero()
+    }
+}
+
+/// Calculates x ** y mod m using Montgomery multiplication.
+pub(super) fn monty_modpow(x: &BigUint, y: &BigUint, m: &BigUint) -> BigUint {
+    MontgomeryContext::new(m).modpow(x, y)
 }
@@ -0,0 +1,222 @@
+use super::barrett::BarrettReducer;
+use super::monty::MontgomeryContext;
+use super::power::window_bits;
+use super::BigUint;
+
+use crate::std_alloc::Vec;
+
+use core::cmp;
+
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+/// Returns `a * b`, where `None` stands for the identity.
+fn mul_opt<T, M>(a: Option<T>, b: Option<&T>, mul: &M) -> Option<T>
+where
+    T: Clone,
+    M: Fn(&T, &T) -> T,
+{
+    match (a, b) {
+        (Some(a), Some(b)) => Some(mul(&a, b)),
+        (None, Some(b)) => Some(b.clone()),
+        (a, None) => a,
+    }
+}
+
+/// Returns the number of multiplications, besides the shared squarings, that
+/// Straus's method takes for exponents of the given bit lengths.
+fn straus_cost(bits: &[u64]) -> u64 {
+    let cost = |b: u64| {
+        let w = window_bits(b);
+        (1 << (w - 1)) + b / (w + 1)
+    };
+    bits.iter().map(|&b| cost(b)).sum()
+}
+
+/// Returns the best window width for Pippenger's method with `count` exponents
+/// of up to `bits` bits, and the number of multiplications it takes.
+fn pippenger_window(count: u64, bits: u64) -> (u64, u64) {
+    (1..=16)
+        .map(|c| (c, Integer::div_ceil(&bits, &c) * (count + (2 << c))))
+        .min_by_key(|&(_, cost)| cost)
+        .unwrap()
+}
+
+/// Calculates the product of `x ** e` over all pairs by Straus's method, also
+/// known as Shamir's trick: each exponent is split into sliding windows as in
+/// `sliding_window_pow`, and the windows of all the exponents are multiplied
+/// into a single accumulator, so they share its squarings.
+///
+/// Returns `None` for the empty product.
+fn straus<T, M, S>(pairs: &[(T, &BigUint)], mul: M, square: S) -> Option<T>
+where
+    T: Clone,
+    M: Fn(&T, &T) -> T,
+    S: Fn(&T) -> T,
+{
+    // The windows as (lowest bit, base, table index), from the highest.
+    let mut windows = Vec::new();
+    let mut tables = Vec::with_capacity(pairs.len());
+    for (k, &(ref x, e)) in pairs.iter().enumerate() {
+        // table[i] contains x^(2i + 1)
+        let w = window_bits(e.bits());
+        let mut table = Vec::with_capacity(1 << (w - 1));
+        table.push(x.clone());
+        if w > 1 {
+            let x2 = square(x);
+            for i in 1..1 << (w - 1) {
+                let next = mul(&table[i - 1], &x2);
+                table.push(next);
+            }
+        }
+        tables.push(table);
+
+        let mut i = e.bits();
+        while i > 0 {
+            if !e.bit(i - 1) {
+                i -= 1;
+                continue;
+            }
+            let mut len = cmp::min(w, i);
+            while !e.bit(i - len) {
+                len -= 1;
+            }
+            let window = (i - len..i)
+                .rev()
+                .fold(0, |v, j| v << 1 | e.bit(j) as usize);
+            windows.push((i - len, k, window >> 1));
+            i -= len;
+        }
+    }
+    windows.sort_by_key(|&(j, _, _)| cmp::Reverse(j));
+
+    let bits = pairs.iter().map(|(_, e)| e.bits()).max().unwrap_or(0);
+    let mut windows = windows.into_iter().peekable();
+    let mut acc = None;
+    for i in (0..bits).rev() {
+        acc = acc.map(|a| square(&a));
+        while let Some(&(j, k, index)) = windows.peek() {
+            if j != i {
+                break;
+            }
+            acc = mul_opt(acc, Some(&tables[k][index]), &mul);
+            windows.next();
+        }
+    }
+    acc
+}
+
+/// Calculates the product of `x ** e` over all pairs by Pippenger's method:
+/// for each window of `c` bits, the bases are gathered into buckets by their
+/// digits `d`, and the product of each `bucket[d] ** d` is found from running
+/// products, with only two multiplications per bucket.
+///
+/// Returns `None` for the empty product.
+fn pippenger<T, M, S>(pairs: &[(T, &BigUint)], c: u64, mul: M, square: S) -> Option<T>
+where
+    T: Clone,
+    M: Fn(&T, &T) -> T,
+    S: Fn(&T) -> T,
+{
+    let bits = pairs.iter().map(|(_, e)| e.bits()).max().unwrap_or(0);
+    let digit = |e: &BigUint, start: u64| {
+        (start..cmp::min(start + c, bits))
+            .rev()
+            .fold(0, |v, j| v << 1 | e.bit(j) as usize)
+    };
+
+    let mut acc = None;
+    for k in (0..Integer::div_ceil(&bits, &c)).rev() {
+        for _ in 0..c {
+            acc = acc.map(|a| square(&a));
+        }
+
+        let mut buckets = vec![None; 1 << c];
+        for &(ref x, e) in pairs {
+            let d = digit(e, k * c);
+            if d != 0 {
+                buckets[d] = mul_opt(buckets[d].take(), Some(x), &mul);
+            }
+        }
+
+        // The running product from the top is multiplied in once for each
+        // bucket at or below it.
+        let mut running = None;
+        let mut total = None;
+        for bucket in buckets.into_iter().skip(1).rev() {
+            running = mul_opt(running, bucket.as_ref(), &mul);
+            total = mul_opt(total, running.as_ref(), &mul);
+        }
+        acc = mul_opt(acc, total.as_ref(), &mul);
+    }
+    acc
+}
+
+/// Calculates the product of `x ** e` over all pairs, by whichever of Straus's
+/// and Pippenger's methods takes fewer multiplications.
+fn multi_pow<T, M, S>(pairs: &[(T, &BigUint)], mul: M, square: S) -> Option<T>
+where
+    T: Clone,
+    M: Fn(&T, &T) -> T,
+    S: Fn(&T) -> T,
+{
+    let bits: Vec<u64> = pairs.iter().map(|(_, e)| e.bits()).collect();
+    let max_bits = bits.iter().cloned().max().unwrap_or(0);
+    let (c, cost) = pippenger_window(bits.len() as u64, max_bits);
+    if cost < straus_cost(&bits) {
+        pippenger(pairs, c, mul, square)
+    } else {
+        straus(pairs, mul, square)
+    }
+}
+
+pub(super) fn multi_modpow(pairs: &[(BigUint, BigUint)], modulus: &BigUint) -> BigUint {
+    assert!(
+        !modulus.is_zero(),
+        "attempt to calculate with zero modulus!"
+    );
+    let pairs = pairs.iter().filter(|(_, e)| !e.is_zero());
+
+    let result = if modulus.is_odd() {
+        let ctx = MontgomeryContext::new(modulus);
+        let pairs: Vec<_> = pairs.map(|(x, e)| (ctx.to_monty(x), e)).collect();
+        multi_pow(&pairs, |a, b| ctx.mul(a, b), |a| ctx.square(a)).map(|r| ctx.from_monty(&r))
+    } else {
+        let reducer = BarrettReducer::new(modulus);
+        let pairs: Vec<_> = pairs.map(|(x, e)| (reducer.reduce(x), e)).collect();
+        multi_pow(
+            &pairs,
+            |a, b| reducer.mul_mod(a, b),
+            |a| reducer.reduce(&a.square()),
+        )
+    };
+    result.unwrap_or_else(|| BigUint::one() % modulus)
+}
+
+#[test]
+fn test_straus_pippenger() {
+    let p = 1_000_003u64;
+    let mul = |a: &u64, b: &u64| a * b % p;
+    let square = |a: &u64| a * a % p;
+
+    let exps: Vec<BigUint> = (0..50u64)
+        .map(|i| (BigUint::one() << (i * 7 % 130)) * (i * i + 3) + i)
+        .collect();
+    for n in 0..exps.len() {
+        let pairs: Vec<_> = exps[..n]
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (i as u64 + 2, e))
+            .collect();
+        let expected = pairs.iter().fold(1u64, |acc, &(x, e)| {
+            let x = BigUint::from(x).modpow(e, &BigUint::from(p));
+            acc * x.iter_u64_digits().next().unwrap_or(0) % p
+        });
+        let expected = if n == 0 { None } else { Some(expected) };
+
+        assert_eq!(straus(&pairs, mul, square), expected);
+        for c in 1..10 {
+            assert_eq!(pippenger(&pairs, c, mul, square), expected);
+        }
+    }
+}
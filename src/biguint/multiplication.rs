@@ -1,4 +1,5 @@
 use super::addition::{__add2, add2};
+use super::ntt;
 use super::subtraction::sub2;
 #[cfg(not(u64_digit))]
 use super::u32_from_u128;
@@ -63,6 +64,13 @@ fn bigint_from_slice(slice: &[BigDigit]) -> BigInt {
     BigInt::from(biguint_from_vec(slice.to_vec()))
 }
 
+/// The smaller operand length, in digits, at which `mac3` switches from Toom-3
+/// to the number-theoretic transform.
+#[cfg(u64_digit)]
+const NTT_THRESHOLD: usize = 2048;
+#[cfg(not(u64_digit))]
+const NTT_THRESHOLD: usize = 4096;
+
 /// Three argument multiply accumulate:
 /// acc += b * c
 #[allow(clippy::many_single_char_names)]
@@ -88,13 +96,15 @@ fn mac3(mut acc: &mut [BigDigit], mut b: &[BigDigit], mut c: &[BigDigit]) {
     let acc = acc;
     let (x, y) = if b.len() < c.len() { (b, c) } else { (c, b) };
 
-    // We use three algorithms for different input sizes.
+    // We use four algorithms for different input sizes.
     //
     // - For small inputs, long multiplication is fastest.
     // - Next we use Karatsuba multiplication (Toom-2), which we have optimized
     //   to avoid unnecessary allocations for intermediate values.
-    // - For the largest inputs we use Toom-3, which better optimizes the
+    // - For larger inputs we use Toom-3, which better optimizes the
     //   number of operations, but uses more temporary allocations.
+    // - For the largest inputs we use a number-theoretic transform, which is
+    //   quasi-linear, but has a large constant overhead.
     //
     // The thresholds are somewhat arbitrary, chosen by evaluating the results
     // of `cargo bench --bench bigint multiply`.
@@ -217,7 +227,7 @@ fn mac3(mut acc: &mut [BigDigit], mut b: &[BigDigit], mut c: &[BigDigit]) {
             }
             NoSign => (),
         }
-    } else {
+    } else if x.len() < NTT_THRESHOLD {
         // Toom-3 multiplication:
         //
         // Toom-3 is like Karatsuba above, but dividing the inputs into three parts.
@@ -299,57 +309,213 @@ fn mac3(mut acc: &mut [BigDigit], mut b: &[BigDigit], mut c: &[BigDigit]) {
         // w(-2)
         let r3 = ((p2 + x2) * 2 - x0) * ((q2 + y2) * 2 - y0);
 
-        // Evaluating these points gives us the following system of linear equations.
-        //
-        //  0  0  0  0  1 | a
-        //  1  1  1  1  1 | b
-        //  1 -1  1 -1  1 | c
-        // 16 -8  4 -2  1 | d
-        //  1  0  0  0  0 | e
-        //
-        // The solved equation (after gaussian elimination or similar)
-        // in terms of its coefficients:
-        //
-        // w0 = w(0)
-        // w1 = w(0)/2 + w(1)/3 - w(-1) + w(2)/6 - 2*w(inf)
-        // w2 = -w(0) + w(1)/2 + w(-1)/2 - w(inf)
-        // w3 = -w(0)/2 + w(1)/6 + w(-1)/2 - w(1)/6
-        // w4 = w(inf)
-        //
-        // This particular sequence is given by Bodrato and is an interpolation
-        // of the above equations.
-        let mut comp3: BigInt = (r3 - &r1) / 3u32;
-        let mut comp1: BigInt = (r1 - &r2) >> 1;
-        let mut comp2: BigInt = r2 - &r0;
-        comp3 = ((&comp2 - comp3) >> 1) + (&r4 << 1);
-        comp2 += &comp1 - &r4;
-        comp1 -= &comp3;
-
-        // Recomposition. The coefficients of the polynomial are now known.
-        //
-        // Evaluate at w(t) where t is our given base to get the result.
-        //
-        //     let bits = u64::from(big_digit::BITS) * i as u64;
-        //     let result = r0
-        //         + (comp1 << bits)
-        //         + (comp2 << (2 * bits))
-        //         + (comp3 << (3 * bits))
-        //         + (r4 << (4 * bits));
-        //     let result_pos = result.to_biguint().unwrap();
-        //     add2(&mut acc[..], &result_pos.data);
-        //
-        // But with less intermediate copying:
-        for (j, result) in [&r0, &comp1, &comp2, &comp3, &r4].iter().enumerate().rev() {
-            match result.sign() {
-                Plus => add2(&mut acc[i * j..], result.digits()),
-                Minus => sub2(&mut acc[i * j..], result.digits()),
-                NoSign => {}
-            }
+        toom3_recompose(acc, i, r0, r1, r2, r3, r4);
+    } else {
+        ntt::mac3_ntt(acc, x, y);
+    }
+}
+
+/// Interpolates the Toom-3 product from its values `w(0), w(1), w(-1), w(-2), w(inf)`,
+/// and adds it to `acc`, where `i` is the number of digits in each part.
+fn toom3_recompose(
+    acc: &mut [BigDigit],
+    i: usize,
+    r0: BigInt,
+    r1: BigInt,
+    r2: BigInt,
+    r3: BigInt,
+    r4: BigInt,
+) {
+    // Evaluating these points gives us the following system of linear equations.
+    //
+    //  0  0  0  0  1 | a
+    //  1  1  1  1  1 | b
+    //  1 -1  1 -1  1 | c
+    // 16 -8  4 -2  1 | d
+    //  1  0  0  0  0 | e
+    //
+    // The solved equation (after gaussian elimination or similar)
+    // in terms of its coefficients:
+    //
+    // w0 = w(0)
+    // w1 = w(0)/2 + w(1)/3 - w(-1) + w(2)/6 - 2*w(inf)
+    // w2 = -w(0) + w(1)/2 + w(-1)/2 - w(inf)
+    // w3 = -w(0)/2 + w(1)/6 + w(-1)/2 - w(1)/6
+    // w4 = w(inf)
+    //
+    // This particular sequence is given by Bodrato and is an interpolation
+    // of the above equations.
+    let mut comp3: BigInt = (r3 - &r1) / 3u32;
+    let mut comp1: BigInt = (r1 - &r2) >> 1;
+    let mut comp2: BigInt = r2 - &r0;
+    comp3 = ((&comp2 - comp3) >> 1) + (&r4 << 1);
+    comp2 += &comp1 - &r4;
+    comp1 -= &comp3;
+
+    // Recomposition. The coefficients of the polynomial are now known.
+    //
+    // Evaluate at w(t) where t is our given base to get the result.
+    //
+    //     let bits = u64::from(big_digit::BITS) * i as u64;
+    //     let result = r0
+    //         + (comp1 << bits)
+    //         + (comp2 << (2 * bits))
+    //         + (comp3 << (3 * bits))
+    //         + (r4 << (4 * bits));
+    //     let result_pos = result.to_biguint().unwrap();
+    //     add2(&mut acc[..], &result_pos.data);
+    //
+    // But with less intermediate copying:
+    for (j, result) in [&r0, &comp1, &comp2, &comp3, &r4].iter().enumerate().rev() {
+        match result.sign() {
+            Plus => add2(&mut acc[i * j..], result.digits()),
+            Minus => sub2(&mut acc[i * j..], result.digits()),
+            NoSign => {}
         }
     }
 }
 
+/// Squaring multiply accumulate:
+/// acc += x * x
+///
+/// This follows the same strategy as `mac3`, but each algorithm is specialized
+/// to take advantage of the symmetry of squaring.
+fn mac3_square(mut acc: &mut [BigDigit], mut x: &[BigDigit]) {
+    // Least-significant zeros have no effect on the output, beyond a shift.
+    if let Some(&0) = x.first() {
+        if let Some(nz) = x.iter().position(|&d| d != 0) {
+            x = &x[nz..];
+            acc = &mut acc[nz * 2..];
+        } else {
+            return;
+        }
+    }
+
+    let acc = acc;
+
+    if x.len() <= 32 {
+        // Long squaring:
+        //
+        // Each cross product x[i] * x[j] with i != j appears twice, so we sum
+        // those with i < j only once, double that, and then add the squares
+        // on the diagonal. The product fits in a buffer on the stack.
+        let mut buf = [0; 64];
+        let p = &mut buf[..x.len() * 2];
+        for (i, &xi) in x.iter().enumerate() {
+            mac_digit(&mut p[i * 2 + 1..], &x[i + 1..], xi);
+        }
+
+        let mut carry = 0;
+        for d in p.iter_mut() {
+            let hi = *d >> (big_digit::BITS - 1);
+            *d = (*d << 1) | carry;
+            carry = hi;
+        }
+        debug_assert_eq!(carry, 0);
+
+        let mut carry: DoubleBigDigit = 0;
+        for (pair, &xi) in p.chunks_mut(2).zip(x) {
+            let (hi, lo) =
+                big_digit::from_doublebigdigit(DoubleBigDigit::from(xi) * DoubleBigDigit::from(xi));
+            pair[0] = mac_with_carry(pair[0], lo, 1, &mut carry);
+            pair[1] = mac_with_carry(pair[1], hi, 1, &mut carry);
+        }
+        debug_assert_eq!(carry, 0);
+
+        add2(acc, p);
+    } else if x.len() <= 256 {
+        // Karatsuba squaring:
+        //
+        // With x = x0 + x1 * b, the Karatsuba identity from `mac3` becomes
+        //
+        // x * x = p0
+        //       + (p0 + p2 - p1) * b
+        //       + p2 * b^2
+        //
+        // where p0 = x0^2, p1 = (x1 - x0)^2, and p2 = x1^2. Every intermediate
+        // product is another square, and p1 is never negative.
+        let b = x.len() / 2;
+        let (x0, x1) = x.split_at(b);
+
+        let len = x1.len() * 2 + 1;
+        let mut p = BigUint { data: vec![0; len] };
+
+        // p2 = x1^2
+        mac3_square(&mut p.data, x1);
+        p.normalize();
+
+        add2(&mut acc[b..], &p.data);
+        add2(&mut acc[b * 2..], &p.data);
+
+        p.data.truncate(0);
+        p.data.resize(len, 0);
+
+        // p0 = x0^2
+        mac3_square(&mut p.data, x0);
+        p.normalize();
+
+        add2(acc, &p.data);
+        add2(&mut acc[b..], &p.data);
+
+        // p1 = (x1 - x0)^2
+        let (j_sign, j) = sub_sign(x1, x0);
+        if j_sign != NoSign {
+            p.data.truncate(0);
+            p.data.resize(len, 0);
+
+            mac3_square(&mut p.data, &j.data);
+            p.normalize();
+
+            sub2(&mut acc[b..], &p.data);
+        }
+    } else if x.len() < NTT_THRESHOLD {
+        // Toom-3 squaring:
+        //
+        // This evaluates x(t)^2 at the same points as the Toom-3 multiplication
+        // in `mac3`, so each of the five products is a square of half the cost.
+        let i = x.len() / 3 + 1;
+
+        let x0_len = i;
+        let x1_len = Ord::min(x.len() - x0_len, i);
+
+        // x(t) = x2*t^2 + x1*t + x0
+        let x0 = bigint_from_slice(&x[..x0_len]);
+        let x1 = bigint_from_slice(&x[x0_len..x0_len + x1_len]);
+        let x2 = bigint_from_slice(&x[x0_len + x1_len..]);
+
+        // x0 + x2, avoiding temporaries
+        let p = &x0 + &x2;
+
+        // x2 - x1 + x0, avoiding temporaries
+        let p2 = &p - &x1;
+
+        // w(0)
+        let r0 = x0.square();
+
+        // w(inf)
+        let r4 = x2.square();
+
+        // w(1)
+        let r1 = (p + x1).square();
+
+        // w(-1)
+        let r2 = p2.square();
+
+        // w(-2)
+        let r3: BigInt = ((p2 + x2) << 1) - x0;
+        let r3 = r3.square();
+
+        toom3_recompose(acc, i, r0, r1, r2, r3, r4);
+    } else {
+        ntt::mac3_square_ntt(acc, x);
+    }
+}
+
 fn mul3(x: &[BigDigit], y: &[BigDigit]) -> BigUint {
+    if x.as_ptr() == y.as_ptr() && x.len() == y.len() {
+        return square3(x);
+    }
+
     let len = x.len() + y.len() + 1;
     let mut prod = BigUint { data: vec![0; len] };
 
@@ -357,6 +523,57 @@ fn mul3(x: &[BigDigit], y: &[BigDigit]) -> BigUint {
     prod.normalized()
 }
 
+/// Returns an estimate of `x * y / 2^(len * BITS)`, for `2 <= len < 2^BITS`,
+/// computing only the digits from `len - 2` on. The digits left out make it
+/// less than the exact quotient by at most 1.
+pub(super) fn mul_high(x: &[BigDigit], y: &[BigDigit], len: usize) -> BigUint {
+    let skip = len - 2;
+    let mut prod = vec![0; (x.len() + y.len() + 1).saturating_sub(skip)];
+    for (i, &xi) in x.iter().enumerate() {
+        let j0 = skip.saturating_sub(i);
+        if xi == 0 || j0 >= y.len() {
+            continue;
+        }
+        let start = i + j0 - skip;
+        let mut carry = 0;
+        for (p, &yj) in prod[start..].iter_mut().zip(&y[j0..]) {
+            *p = mac_with_carry(*p, yj, xi, &mut carry);
+        }
+        __add2(&mut prod[start + y.len() - j0..], &[carry as BigDigit]);
+    }
+    prod.drain(..Ord::min(2, prod.len()));
+    biguint_from_vec(prod)
+}
+
+/// Returns `x * y mod 2^(len * BITS)`, computing only the digits below `len`,
+/// which takes about half the work of the full product for `len` near the
+/// lengths of `x` and `y`.
+pub(super) fn mul_low(x: &[BigDigit], y: &[BigDigit], len: usize) -> BigUint {
+    let mut prod = vec![0; len];
+    for (i, &xi) in x.iter().enumerate().take(len) {
+        if xi == 0 {
+            continue;
+        }
+        let mut carry = 0;
+        for (p, &yj) in prod[i..].iter_mut().zip(y) {
+            *p = mac_with_carry(*p, yj, xi, &mut carry);
+        }
+        if i + y.len() < len {
+            // Any carry out of the top digit is truncated away.
+            __add2(&mut prod[i + y.len()..], &[carry as BigDigit]);
+        }
+    }
+    biguint_from_vec(prod)
+}
+
+pub(super) fn square3(x: &[BigDigit]) -> BigUint {
+    let len = x.len() * 2 + 1;
+    let mut prod = BigUint { data: vec![0; len] };
+
+    mac3_square(&mut prod.data, x);
+    prod.normalized()
+}
+
 fn scalar_mul(a: &mut BigUint, b: BigDigit) {
     match b {
         0 => a.set_zero(),
@@ -566,3 +783,74 @@ fn test_sub_sign() {
     assert_eq!(sub_sign_i(&a.data, &b.data), &a_i - &b_i);
     assert_eq!(sub_sign_i(&b.data, &a.data), &b_i - &a_i);
 }
+
+#[test]
+fn test_mac3_ntt() {
+    use crate::std_alloc::Vec;
+
+    fn long_mul(x: &[BigDigit], y: &[BigDigit]) -> Vec<BigDigit> {
+        let mut prod = vec![0; x.len() + y.len() + 1];
+        for (i, &yi) in y.iter().enumerate() {
+            mac_digit(&mut prod[i..], x, yi);
+        }
+        prod
+    }
+
+    fn check(x: &[BigDigit], y: &[BigDigit]) {
+        let mut prod = vec![0; x.len() + y.len() + 1];
+        ntt::mac3_ntt(&mut prod, x, y);
+        assert_eq!(prod, long_mul(x, y));
+    }
+
+    let mut state: BigDigit = 1;
+    let mut digits = |n: usize| -> Vec<BigDigit> {
+        (0..n)
+            .map(|_| {
+                // a simple linear congruential generator
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                state
+            })
+            .collect()
+    };
+    for &(m, n) in &[(1, 1), (3, 500), (100, 100), (257, 700), (512, 512)] {
+        let x = digits(m);
+        let y = digits(n);
+        check(&x, &y);
+    }
+
+    // The largest coefficients come from digits that are all ones.
+    let ones = vec![!0; 1000];
+    check(&ones, &ones);
+}
+
+#[test]
+fn test_mul_low_high() {
+    use crate::std_alloc::Vec;
+
+    let mut state: BigDigit = 1;
+    let mut digits = |n: usize| -> Vec<BigDigit> {
+        (0..n)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                state
+            })
+            .collect()
+    };
+    let ones = vec![!0; 40];
+    for &(m, n) in &[(1, 1), (3, 5), (20, 20), (21, 40)] {
+        for (x, y) in &[
+            (digits(m), digits(n)),
+            (ones[..m].to_vec(), ones[..n].to_vec()),
+        ] {
+            let prod = mul3(x, y);
+            for len in 2..m + n + 2 {
+                let bits = len as u64 * u64::from(big_digit::BITS);
+                let low = &prod % (BigUint::one() << bits);
+                assert_eq!(mul_low(x, y, len), low);
+                let high = mul_high(x, y, len);
+                let exact = &prod >> bits;
+                assert!(high <= exact && exact <= high + 1u32);
+            }
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Multiplication by number-theoretic transform, for very large operands.
+//!
+//! The operands are split into 64-bit words and convolved modulo three primes
+//! just below 2⁶³, whose product is large enough to hold every coefficient of
+//! the exact convolution.  The coefficients are then recovered with Garner's
+//! form of the Chinese remainder theorem and carried into the final product.
+
+use super::addition::add2;
+#[cfg(not(u64_digit))]
+use super::u32_chunk_to_u64;
+
+use crate::big_digit::BigDigit;
+use crate::std_alloc::{Cow, Vec};
+
+/// A prime `p = c * 2⁴⁶ + 1 < 2⁶³`, which supports transforms of any power-of-two
+/// length up to 2⁴⁶, with the constants needed for Montgomery arithmetic mod `p`.
+struct Prime {
+    p: u64,
+    /// `-p⁻¹ mod 2⁶⁴`
+    pinv: u64,
+    /// `2¹²⁸ mod p`
+    r2: u64,
+    /// A primitive root mod `p`
+    g: u64,
+}
+
+const P1: Prime = Prime {
+    p: 0x7fe7_c000_0000_0001,
+    pinv: 0x7fe7_bfff_ffff_ffff,
+    r2: 0x5ad2_ad9c_040e_c4d1,
+    g: 3,
+};
+
+const P2: Prime = Prime {
+    p: 0x7fe4_c000_0000_0001,
+    pinv: 0x7fe4_bfff_ffff_ffff,
+    r2: 0x655a_a8c6_8e45_49c5,
+    g: 3,
+};
+
+const P3: Prime = Prime {
+    p: 0x7fe1_0000_0000_0001,
+    pinv: 0x7fe0_ffff_ffff_ffff,
+    r2: 0x4f97_e1f0_b84c_a294,
+    g: 3,
+};
+
+// Constants for Garner's algorithm, with the modular ones in Montgomery form.
+/// `p1⁻¹ * 2⁶⁴ mod p2`
+const P1_INV_P2: u64 = 0x2aa1_9555_5555_aaab;
+/// `p1 * 2⁶⁴ mod p3`
+const P1_P3: u64 = 0x5b38_7cba_3518_dc06;
+/// `(p1 * p2)⁻¹ * 2⁶⁴ mod p3`
+const P1P2_INV_P3: u64 = 0x3d41_6d5a_5ed1_d13a;
+/// `p1 * p2`
+const P1P2: u128 = 0x3fe6_4294_d000_0000_ffcc_8000_0000_0001;
+
+/// The largest transform length supported by all three primes.
+const MAX_LEN_BITS: u32 = 46;
+
+impl Prime {
+    /// Given `-p < x < p` in two's complement, returns `x mod p`.
+    ///
+    /// This is written without branches, as the comparison is unpredictable.
+    #[inline]
+    fn fix(&self, x: u64) -> u64 {
+        // Since p < 2⁶³, only negative values have the high bit set.
+        let mask = 0u64.wrapping_sub(x >> 63);
+        x.wrapping_add(self.p & mask)
+    }
+
+    #[inline]
+    fn add(&self, a: u64, b: u64) -> u64 {
+        self.fix((a + b).wrapping_sub(self.p))
+    }
+
+    #[inline]
+    fn sub(&self, a: u64, b: u64) -> u64 {
+        self.fix(a.wrapping_sub(b))
+    }
+
+    /// Montgomery multiplication: `a * b * 2⁻⁶⁴ mod p`
+    #[inline]
+    fn mul(&self, a: u64, b: u64) -> u64 {
+        // Since p < 2⁶³, none of these can overflow.
+        let t = u128::from(a) * u128::from(b);
+        let m = (t as u64).wrapping_mul(self.pinv);
+        let u = ((t + u128::from(m) * u128::from(self.p)) >> 64) as u64;
+        self.fix(u.wrapping_sub(self.p))
+    }
+
+    /// Converts a plain value into Montgomery form.
+    #[inline]
+    fn to_monty(&self, a: u64) -> u64 {
+        self.mul(a % self.p, self.r2)
+    }
+
+    /// Exponentiation of a value in Montgomery form.
+    fn pow(&self, mut base: u64, mut exp: u64) -> u64 {
+        let mut acc = self.to_monty(1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                acc = self.mul(acc, base);
+            }
+            base = self.mul(base, base);
+            exp >>= 1;
+        }
+        acc
+    }
+
+    /// Returns the powers `wʲ` for `j < n/2`, in Montgomery form, where `w` is
+    /// a principal `n`th root of unity, or its inverse.
+    fn roots(&self, n: usize, inverse: bool) -> Vec<u64> {
+        let mut w = self.pow(self.to_monty(self.g), (self.p - 1) / n as u64);
+        if inverse {
+            w = self.pow(w, n as u64 - 1);
+        }
+        let mut roots = Vec::with_capacity(n / 2);
+        let mut x = self.to_monty(1);
+        for _ in 0..n / 2 {
+            roots.push(x);
+            x = self.mul(x, w);
+        }
+        roots
+    }
+
+    /// Forward transform, decimation in frequency (Gentleman–Sande).
+    ///
+    /// The input is in natural order, and the output is in bit-reversed order.
+    fn forward(&self, a: &mut [u64], roots: &[u64]) {
+        let n = a.len();
+        let mut len = n;
+        while len >= 2 {
+            let half = len / 2;
+            let stride = n / len;
+            for chunk in a.chunks_mut(len) {
+                let (lo, hi) = chunk.split_at_mut(half);
+                for (j, (x, y)) in lo.iter_mut().zip(hi).enumerate() {
+                    let (u, v) = (*x, *y);
+                    *x = self.add(u, v);
+                    *y = self.mul(self.sub(u, v), roots[j * stride]);
+                }
+            }
+            len = half;
+        }
+    }
+
+    /// Inverse transform, decimation in time (Cooley–Tukey), without scaling.
+    ///
+    /// The input is in bit-reversed order, and the output is in natural order.
+    fn inverse(&self, a: &mut [u64], roots: &[u64]) {
+        let n = a.len();
+        let mut len = 2;
+        while len <= n {
+            let half = len / 2;
+            let stride = n / len;
+            for chunk in a.chunks_mut(len) {
+                let (lo, hi) = chunk.split_at_mut(half);
+                for (j, (x, y)) in lo.iter_mut().zip(hi).enumerate() {
+                    let u = *x;
+                    let v = self.mul(*y, roots[j * stride]);
+                    *x = self.add(u, v);
+                    *y = self.sub(u, v);
+                }
+            }
+            len *= 2;
+        }
+    }
+
+    /// Copies the words into a buffer of length `n`, reduced mod `p`.
+    fn load(&self, x: &[u64], n: usize) -> Vec<u64> {
+        let mut a = Vec::with_capacity(n);
+        a.extend(x.iter().map(|&w| w % self.p));
+        a.resize(n, 0);
+        a
+    }
+
+    /// Computes the cyclic convolution of `x` and `y` mod `p`, with length `n`.
+    fn convolve(&self, x: &[u64], y: &[u64], n: usize) -> Vec<u64> {
+        let roots = self.roots(n, false);
+        let mut a = self.load(x, n);
+        self.forward(&mut a, &roots);
+
+        let mut b = self.load(y, n);
+        self.forward(&mut b, &roots);
+        drop(roots);
+
+        for (x, &y) in a.iter_mut().zip(&b) {
+            *x = self.mul(*x, y);
+        }
+        drop(b);
+
+        self.finish(a)
+    }
+
+    /// Computes the cyclic convolution of `x` with itself mod `p`, with length `n`.
+    fn autoconvolve(&self, x: &[u64], n: usize) -> Vec<u64> {
+        let roots = self.roots(n, false);
+        let mut a = self.load(x, n);
+        self.forward(&mut a, &roots);
+        drop(roots);
+
+        for x in &mut a {
+            *x = self.mul(*x, *x);
+        }
+
+        self.finish(a)
+    }
+
+    /// Transforms the pointwise products back into the convolution.
+    fn finish(&self, mut a: Vec<u64>) -> Vec<u64> {
+        let n = a.len();
+        let iroots = self.roots(n, true);
+        self.inverse(&mut a, &iroots);
+
+        // The inputs were plain values, not Montgomery form, so the products have
+        // an extra factor of 2⁻⁶⁴, and the inverse transform adds a factor of `n`.
+        // Remove both with a single multiplication by `2¹²⁸ / n`.
+        let ninv = self.p - (self.p - 1) / n as u64;
+        let scale = self.mul(self.mul(self.r2, self.r2), ninv);
+        for x in &mut a {
+            *x = self.mul(*x, scale);
+        }
+        a
+    }
+}
+
+/// Recovers a coefficient from its residues, returning it as three 64-bit words.
+#[inline]
+fn garner(r1: u64, r2: u64, r3: u64) -> (u64, u64, u64) {
+    // x = r1 + p1 * t2 + p1 * p2 * t3
+    let r1_2 = P2.fix(r1.wrapping_sub(P2.p));
+    let t2 = P2.mul(P2.sub(r2, r1_2), P1_INV_P2);
+
+    let r1_3 = P3.fix(r1.wrapping_sub(P3.p));
+    let t2_3 = P3.fix(t2.wrapping_sub(P3.p));
+    let y_3 = P3.add(r1_3, P3.mul(t2_3, P1_P3));
+    let t3 = P3.mul(P3.sub(r3, y_3), P1P2_INV_P3);
+
+    let y = u128::from(r1) + u128::from(P1.p) * u128::from(t2);
+    let lo = y + (P1P2 as u64 as u128) * u128::from(t3);
+    let hi = ((P1P2 >> 64) as u64 as u128) * u128::from(t3) + (lo >> 64);
+    (lo as u64, hi as u64, (hi >> 64) as u64)
+}
+
+#[cfg(u64_digit)]
+fn to_words(x: &[BigDigit]) -> Cow<'_, [u64]> {
+    Cow::Borrowed(x)
+}
+
+#[cfg(not(u64_digit))]
+fn to_words(x: &[BigDigit]) -> Cow<'_, [u64]> {
+    Cow::Owned(x.chunks(2).map(u32_chunk_to_u64).collect())
+}
+
+#[cfg(u64_digit)]
+fn from_words(words: Vec<u64>) -> Vec<BigDigit> {
+    words
+}
+
+#[cfg(not(u64_digit))]
+fn from_words(words: Vec<u64>) -> Vec<BigDigit> {
+    let mut digits = Vec::with_capacity(words.len() * 2);
+    for w in words {
+        digits.push(w as BigDigit);
+        digits.push((w >> 32) as BigDigit);
+    }
+    digits
+}
+
+/// Combines the residues of the convolution into the `len` words of the product.
+fn recover(c1: &[u64], c2: &[u64], c3: &[u64], len: usize) -> Vec<u64> {
+    let mut words = Vec::with_capacity(len);
+
+    // Each coefficient is less than 2¹⁸⁹, so the carry always fits in two words.
+    let (mut carry0, mut carry1) = (0u64, 0u64);
+    for ((&r1, &r2), &r3) in c1.iter().zip(c2).zip(c3).take(len) {
+        let (x0, x1, x2) = garner(r1, r2, r3);
+
+        let s0 = u128::from(carry0) + u128::from(x0);
+        let s1 = u128::from(carry1) + u128::from(x1) + (s0 >> 64);
+        words.push(s0 as u64);
+        carry0 = s1 as u64;
+        carry1 = x2 + (s1 >> 64) as u64;
+    }
+    debug_assert!(carry0 == 0 && carry1 == 0);
+    words
+}
+
+/// Returns the transform length for a product of `len` words.
+fn transform_len(len: usize) -> usize {
+    let n = len.next_power_of_two();
+    assert!(
+        n.trailing_zeros() <= MAX_LEN_BITS,
+        "transform length overflow during multiplication!"
+    );
+    n
+}
+
+/// Adds the product with the given residues to `acc`.
+fn accumulate(acc: &mut [BigDigit], c1: &[u64], c2: &[u64], c3: &[u64], len: usize) {
+    let mut prod = from_words(recover(c1, c2, c3, len));
+    while let Some(&0) = prod.last() {
+        prod.pop();
+    }
+    add2(acc, &prod);
+}
+
+/// Three argument multiply accumulate:
+/// acc += x * y
+pub(super) fn mac3_ntt(acc: &mut [BigDigit], x: &[BigDigit], y: &[BigDigit]) {
+    let x = to_words(x);
+    let y = to_words(y);
+
+    let len = x.len() + y.len();
+    let n = transform_len(len);
+
+    let c1 = P1.convolve(&x, &y, n);
+    let c2 = P2.convolve(&x, &y, n);
+    let c3 = P3.convolve(&x, &y, n);
+    accumulate(acc, &c1, &c2, &c3, len);
+}
+
+/// Squaring multiply accumulate:
+/// acc += x * x
+pub(super) fn mac3_square_ntt(acc: &mut [BigDigit], x: &[BigDigit]) {
+    let x = to_words(x);
+
+    let len = x.len() * 2;
+    let n = transform_len(len);
+
+    let c1 = P1.autoconvolve(&x, n);
+    let c2 = P2.autoconvolve(&x, n);
+    let c3 = P3.autoconvolve(&x, n);
+    accumulate(acc, &c1, &c2, &c3, len);
+}
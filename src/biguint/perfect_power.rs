@@ -0,0 +1,176 @@
+use super::division::rem_digit;
+use super::factorial::primes_up_to;
+use super::prime::pow_mod_u64;
+use super::BigUint;
+
+use crate::std_alloc::Vec;
+
+use core::cmp;
+
+use num_traits::{One, Pow, ToPrimitive};
+
+/// Bit masks of the squares modulo 64, 63, 65 and 11. Only about one in 120
+/// non-squares pass all these filters.
+const SQUARES_MOD_64: u64 = 0x0202_0212_0203_0213;
+const SQUARES_MOD_63: u64 = 0x0402_4830_1245_0293;
+const SQUARES_MOD_65: u128 = 0x1_218a_0198_6601_4613;
+const SQUARES_MOD_11: u64 = 0x23b;
+
+/// Returns `true` if `x` may be a square, by its residues.
+fn may_be_square(x: &BigUint) -> bool {
+    let low = x.data.first().map_or(0, |&d| d & 63);
+    if SQUARES_MOD_64 >> low & 1 == 0 {
+        return false;
+    }
+    let r = rem_digit(x, 63 * 65 * 11);
+    SQUARES_MOD_63 >> (r % 63) & 1 == 1
+        && SQUARES_MOD_65 >> (r % 65) & 1 == 1
+        && SQUARES_MOD_11 >> (r % 11) & 1 == 1
+}
+
+pub(super) fn is_perfect_square(x: &BigUint) -> bool {
+    may_be_square(x) && exact_root(x, 2).is_some()
+}
+
+/// Returns the `n`th root of `x` if it's exact.
+fn exact_root(x: &BigUint, n: u32) -> Option<BigUint> {
+    let root = x.nth_root(n);
+    if Pow::pow(&root, n) == *x {
+        Some(root)
+    } else {
+        None
+    }
+}
+
+/// The number of primes `q` that `may_be_power` checks for each exponent.
+const FILTER_PRIMES: usize = 4;
+
+/// The number of exponents whose filter primes are taken together, so the
+/// residues modulo all of them come from a single division.
+const FILTER_BATCH: usize = 64;
+
+/// Returns the first few primes `q = 1 (mod p)` for the prime `p`, by trial
+/// division by `primes`, so only up to the square of the last of those.
+fn filter_primes(p: u64, primes: &[u64]) -> Vec<u64> {
+    let last = *primes.last().unwrap();
+    let limit = last.saturating_mul(last);
+    (1..)
+        .map(|k| 2 * k * p + 1)
+        .take_while(|&q| q <= limit)
+        .filter(|&q| {
+            primes
+                .iter()
+                .take_while(|&&d| d * d <= q)
+                .all(|&d| q % d != 0)
+        })
+        .take(FILTER_PRIMES)
+        .collect()
+}
+
+/// Returns the residues of `x` modulo each of `moduli`, from its remainder
+/// modulo their product rather than a pass over all of `x` for each.
+fn residues(x: &BigUint, moduli: &[u64]) -> Vec<u64> {
+    let product = moduli.iter().fold(BigUint::one(), |acc, &q| acc * q);
+    let r = x % product;
+    moduli.iter().map(|&q| (&r % q).to_u64().unwrap()).collect()
+}
+
+/// Returns `true` if `x` may be a `p`th power for the odd prime `p`, by checking
+/// that it's a `p`th power residue modulo the `filters`, primes `q = 1 (mod p)`.
+/// Each of these rejects all but about `1/p` of the non-powers, so most are
+/// rejected by the first, for which the residue `r` of `x` is given.
+fn may_be_power(x: &BigUint, p: u64, filters: &[u64], r: u64) -> bool {
+    let is_power_residue = |q: u64, r: u64| r == 0 || pow_mod_u64(r, (q - 1) / p, q) == 1;
+    match filters.split_first() {
+        Some((&q, rest)) => {
+            is_power_residue(q, r)
+                && rest
+                    .iter()
+                    .all(|&q| is_power_residue(q, (x % q).to_u64().unwrap()))
+        }
+        None => true,
+    }
+}
+
+pub(super) fn perfect_power(x: &BigUint) -> Option<(BigUint, u32)> {
+    let mut base = x.clone();
+    let mut exp = 1;
+
+    // Take every prime root that is exact, so the exponents multiply up to the
+    // largest. A `p`th power of at least 2 has more than `p` bits. The primes
+    // go a little further for small `x`, to test the filter primes.
+    let primes = primes_up_to(cmp::max(x.bits(), 1 << 10));
+    let mut i = 0;
+    while i < primes.len() && primes[i] < base.bits() {
+        let bits = base.bits();
+        let batch: Vec<u64> = primes[i..]
+            .iter()
+            .cloned()
+            .take(FILTER_BATCH)
+            .take_while(|&p| p < bits)
+            .collect();
+        let filters: Vec<Vec<u64>> = batch.iter().map(|&p| filter_primes(p, &primes)).collect();
+        // An exponent without filter primes just gets a residue modulo 1.
+        let firsts: Vec<u64> = filters
+            .iter()
+            .map(|qs| qs.first().map_or(1, |&q| q))
+            .collect();
+        let residues = residues(&base, &firsts);
+
+        // The exponent must also divide the number of trailing zeros.
+        let zeros = base.trailing_zeros().unwrap();
+        let mut root = None;
+        for (k, &p) in batch.iter().enumerate() {
+            let may_be = if p == 2 {
+                may_be_square(&base)
+            } else {
+                may_be_power(&base, p, &filters[k], residues[k])
+            };
+            if zeros % p == 0 && may_be {
+                root = exact_root(&base, p as u32);
+                if root.is_some() {
+                    break;
+                }
+            }
+            i += 1;
+        }
+
+        // Try the same exponent again on the root.
+        if let Some(root) = root {
+            base = root;
+            exp *= primes[i] as u32;
+        }
+    }
+
+    if exp > 1 {
+        Some((base, exp))
+    } else {
+        None
+    }
+}
+
+#[test]
+fn test_may_be_square() {
+    let squares = (0u32..2000).filter(|&x| may_be_square(&BigUint::from(x)));
+    assert!(squares.count() < 2000 / 20);
+    for x in 0u32..2000 {
+        assert!(may_be_square(&BigUint::from(x * x)));
+    }
+}
+
+#[test]
+fn test_may_be_power() {
+    let primes = primes_up_to(1 << 10);
+    for &p in &[3u32, 5, 7, 31] {
+        let filters = filter_primes(u64::from(p), &primes);
+        let may_be = |x: &BigUint| {
+            let r = residues(x, &filters[..1])[0];
+            may_be_power(x, u64::from(p), &filters, r)
+        };
+        let powers = (0u32..2000).filter(|&x| may_be(&BigUint::from(x)));
+        assert!(powers.count() < 2000 / 20);
+        for x in 0u32..100 {
+            assert!(may_be(&Pow::pow(BigUint::from(x), p)));
+        }
+    }
+}
@@ -1,7 +1,11 @@
+use super::gcd::modinv;
 use super::monty::monty_modpow;
 use super::BigUint;
 
 use crate::big_digit::{self, BigDigit};
+use crate::std_alloc::Vec;
+
+use core::cmp;
 
 use num_integer::Integer;
 use num_traits::{One, Pow, ToPrimitive, Zero};
@@ -72,7 +76,7 @@ macro_rules! pow_impl {
                 let mut base = self;
 
                 while exp & 1 == 0 {
-                    base = &base * &base;
+                    base = base.square();
                     exp >>= 1;
                 }
 
@@ -83,7 +87,7 @@ macro_rules! pow_impl {
                 let mut acc = base.clone();
                 while exp > 1 {
                     exp >>= 1;
-                    base = &base * &base;
+                    base = base.square();
                     if exp & 1 == 1 {
                         acc *= &base;
                     }
@@ -141,80 +145,160 @@ pub(super) fn modpow(x: &BigUint, exponent: &BigUint, modulus: &BigUint) -> BigU
         // For an odd modulus, we can use Montgomery multiplication in base 2^32.
         monty_modpow(x, exponent, modulus)
     } else {
-        // Otherwise do basically the same as `num::pow`, but with a modulus.
-        plain_modpow(x, &exponent.data, modulus)
+        // Otherwise split off the power of two, which only needs masking,
+        // and recombine with the odd part by the Chinese remainder theorem.
+        crt_modpow(x, exponent, modulus)
     }
 }
 
-fn plain_modpow(base: &BigUint, exp_data: &[BigDigit], modulus: &BigUint) -> BigUint {
-    assert!(
-        !modulus.is_zero(),
-        "attempt to calculate with zero modulus!"
-    );
+/// Returns `x mod 2^bits`.
+fn truncate_bits(mut x: BigUint, bits: u64) -> BigUint {
+    let digit_bits = u64::from(big_digit::BITS);
+    let len = Integer::div_ceil(&bits, &digit_bits) as usize;
+    if x.data.len() >= len {
+        x.data.truncate(len);
+        let rem = bits % digit_bits;
+        if rem != 0 {
+            x.data[len - 1] &= (1 << rem) - 1;
+        }
+        x.normalize();
+    }
+    x
+}
 
-    let i = match exp_data.iter().position(|&r| r != 0) {
-        None => return BigUint::one(),
-        Some(i) => i,
-    };
+/// Calculates `x ** y mod 2^k`, where the reduction is just a truncation.
+fn pow2_modpow(x: &BigUint, y: &BigUint, k: u64) -> BigUint {
+    let x = truncate_bits(x.clone(), k);
+    if y.is_zero() {
+        return BigUint::one();
+    }
 
-    let mut base = base % modulus;
-    for _ in 0..i {
-        for _ in 0..big_digit::BITS {
-            base = &base * &base % modulus;
+    let y = if x.is_even() {
+        // Any `k` factors of two make the result zero.
+        if x.is_zero() || *y >= BigUint::from(k) {
+            return BigUint::zero();
         }
+        y.clone()
+    } else {
+        // The odd residues modulo 2^k form a group whose order divides 2^k.
+        truncate_bits(y.clone(), k)
+    };
+    if y.is_zero() {
+        return BigUint::one();
     }
 
-    let mut r = exp_data[i];
-    let mut b = 0u8;
-    while r.is_even() {
-        base = &base * &base % modulus;
-        r >>= 1;
-        b += 1;
+    sliding_window_pow(
+        x,
+        &y.data,
+        |a, b| truncate_bits(a * b, k),
+        |a| truncate_bits(a.square(), k),
+    )
+}
+
+/// Calculates `x ** y mod m` for an even `m = 2^k * q`, from the power modulo
+/// the odd `q` using Montgomery multiplication, and the power modulo `2^k`.
+fn crt_modpow(x: &BigUint, y: &BigUint, m: &BigUint) -> BigUint {
+    let k = m.trailing_zeros().unwrap();
+    let q = m >> k;
+    let r2 = pow2_modpow(x, y, k);
+    if q.is_one() {
+        return r2;
     }
+    let r1 = monty_modpow(x, y, &q);
+
+    // Garner's formula: x = r1 + q * ((r2 - r1) * q^-1 mod 2^k)
+    let q_inv = modinv(&q, &(BigUint::one() << k)).unwrap();
+    let diff = (BigUint::one() << k) + r2 - truncate_bits(r1.clone(), k);
+    let t = truncate_bits(diff * q_inv, k);
+    r1 + q * t
+}
+
+/// Does basically the same as `num::pow`, but with a modulus.
+#[cfg(test)]
+fn plain_modpow(base: &BigUint, exp_data: &[BigDigit], modulus: &BigUint) -> BigUint {
+    assert!(
+        !modulus.is_zero(),
+        "attempt to calculate with zero modulus!"
+    );
 
-    let mut exp_iter = exp_data[i + 1..].iter();
-    if exp_iter.len() == 0 && r.is_one() {
-        return base;
+    if exp_data.iter().all(Zero::is_zero) {
+        return BigUint::one();
     }
 
-    let mut acc = base.clone();
-    r >>= 1;
-    b += 1;
+    let base = base % modulus;
+    sliding_window_pow(
+        base,
+        exp_data,
+        |x, y| x * y % modulus,
+        |x| x.square() % modulus,
+    )
+}
 
-    {
-        let mut unit = |exp_is_odd| {
-            base = &base * &base % modulus;
-            if exp_is_odd {
-                acc *= &base;
-                acc %= modulus;
-            }
-        };
+/// Returns the sliding window width to use for an exponent of `bits` bits,
+/// balancing the table precomputation against the multiplications it saves.
+pub(super) fn window_bits(bits: u64) -> u64 {
+    match bits {
+        0..=23 => 1,
+        24..=79 => 3,
+        80..=239 => 4,
+        240..=671 => 5,
+        _ => 6,
+    }
+}
 
-        if let Some(&last) = exp_iter.next_back() {
-            // consume exp_data[i]
-            for _ in b..big_digit::BITS {
-                unit(r.is_odd());
-                r >>= 1;
-            }
+/// Calculates `x ** exp` by left-to-right sliding window exponentiation,
+/// using the given multiplication and squaring. The exponent must be non-zero.
+pub(super) fn sliding_window_pow<T, M, S>(x: T, exp_data: &[BigDigit], mul: M, square: S) -> T
+where
+    T: Clone,
+    M: Fn(&T, &T) -> T,
+    S: Fn(&T) -> T,
+{
+    let digit_bits = u64::from(big_digit::BITS);
+    let bit = |i: u64| (exp_data[(i / digit_bits) as usize] >> (i % digit_bits)) & 1 == 1;
+    let top = exp_data.iter().rposition(|&d| d != 0).unwrap();
+    let bits = (top as u64 + 1) * digit_bits - u64::from(exp_data[top].leading_zeros());
+
+    // table[i] contains x^(2i + 1)
+    let w = window_bits(bits);
+    let mut table = Vec::with_capacity(1 << (w - 1));
+    table.push(x);
+    if w > 1 {
+        let x2 = square(&table[0]);
+        for i in 1..1 << (w - 1) {
+            let next = mul(&table[i - 1], &x2);
+            table.push(next);
+        }
+    }
 
-            // consume all other digits before the last
-            for &r in exp_iter {
-                let mut r = r;
-                for _ in 0..big_digit::BITS {
-                    unit(r.is_odd());
-                    r >>= 1;
-                }
-            }
-            r = last;
+    // The leading bit is set, so the first window initializes the accumulator.
+    let mut acc: Option<T> = None;
+    let mut i = bits;
+    while i > 0 {
+        if !bit(i - 1) {
+            acc = acc.map(|a| square(&a));
+            i -= 1;
+            continue;
         }
 
-        debug_assert_ne!(r, 0);
-        while !r.is_zero() {
-            unit(r.is_odd());
-            r >>= 1;
+        // Take the longest window of up to `w` bits that ends with a set bit.
+        let mut len = cmp::min(w, i);
+        while !bit(i - len) {
+            len -= 1;
         }
+        let window = (i - len..i).rev().fold(0, |v, j| v << 1 | bit(j) as usize);
+        acc = Some(match acc {
+            None => table[window >> 1].clone(),
+            Some(mut a) => {
+                for _ in 0..len {
+                    a = square(&a);
+                }
+                mul(&a, &table[window >> 1])
+            }
+        });
+        i -= len;
     }
-    acc
+    acc.unwrap()
 }
 
 #[test]
@@ -249,6 +333,21 @@ fn test_plain_modpow() {
     );
 }
 
+#[test]
+fn test_crt_modpow() {
+    let base = BigUint::from(3u32).pow(300u32) - 1u32;
+    let odd = (BigUint::from(7u32).pow(100u32) + 2u32) | BigUint::one();
+    for &k in &[1u32, 5, 32, 64, 97, 500] {
+        for m in &[&odd << k, BigUint::one() << k] {
+            for exp in &[base.clone(), BigUint::from(k), BigUint::from(k + 1)] {
+                for x in &[base.clone(), &base << 3, &base - 1u32] {
+                    assert_eq!(crt_modpow(x, exp, m), plain_modpow(x, &exp.data, m));
+                }
+            }
+        }
+    }
+}
+
 #[test]
 fn test_pow_biguint() {
     let base = BigUint::from(5u8);
@@ -256,3 +355,23 @@ fn test_pow_biguint() {
 
     assert_eq!(BigUint::from(125u8), base.pow(exponent));
 }
+
+#[test]
+fn test_sliding_window_pow() {
+    // With addition in place of multiplication, "x ** exp" for x = 1 is just exp.
+    fn check(exp: &BigUint) {
+        let add = sliding_window_pow(BigUint::one(), &exp.data, |x, y| x + y, |x| x << 1);
+        assert_eq!(&add, exp);
+    }
+
+    for e in 1u32..3000 {
+        check(&BigUint::from(e));
+    }
+    for &bits in &[64u32, 100, 240, 671, 1000, 4096] {
+        let e = BigUint::one() << bits;
+        check(&e);
+        check(&(&e - 1u32));
+        check(&(&e / 3u32));
+        check(&(&e / 7u32 + 12345u32));
+    }
+}
@@ -0,0 +1,498 @@
+use super::division::rem_digit;
+use super::gcd::jacobi;
+use super::monty::{MontgomeryContext, MontgomeryElement};
+use super::BigUint;
+
+use crate::big_digit::{self, BigDigit, DoubleBigDigit};
+use crate::std_alloc::Vec;
+
+use num_integer::Integer;
+use num_traits::ToPrimitive;
+
+/// The primes below 1024, used for trial division.
+static SMALL_PRIMES: [u16; 172] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+    197, 199, 211, 223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271, 277, 281, 283, 293, 307,
+    311, 313, 317, 331, 337, 347, 349, 353, 359, 367, 373, 379, 383, 389, 397, 401, 409, 419, 421,
+    431, 433, 439, 443, 449, 457, 461, 463, 467, 479, 487, 491, 499, 503, 509, 521, 523, 541, 547,
+    557, 563, 569, 571, 577, 587, 593, 599, 601, 607, 613, 617, 619, 631, 641, 643, 647, 653, 659,
+    661, 673, 677, 683, 691, 701, 709, 719, 727, 733, 739, 743, 751, 757, 761, 769, 773, 787, 797,
+    809, 811, 821, 823, 827, 829, 839, 853, 857, 859, 863, 877, 881, 883, 887, 907, 911, 919, 929,
+    937, 941, 947, 953, 967, 971, 977, 983, 991, 997, 1009, 1013, 1019, 1021,
+];
+
+/// Miller–Rabin with the first 12 prime bases is deterministic for `n < 2^64`,
+/// and with the first 13 prime bases for `n` below `MR_DETERMINISTIC_BOUND`.
+/// See Sorenson and Webster, "Strong pseudoprimes to twelve prime bases".
+const MR_DETERMINISTIC_BASES: usize = 13;
+
+/// 3317044064679887385961981, the smallest strong pseudoprime to the first 13 prime bases.
+const MR_DETERMINISTIC_BOUND: u128 = 3_317_044_064_679_887_385_961_981;
+
+/// Splits the small primes into groups whose product fits in a digit, so the
+/// residues modulo a whole group take only one pass over a number's digits.
+struct PrimeGroups {
+    rest: &'static [u16],
+}
+
+impl PrimeGroups {
+    fn new(primes: &'static [u16]) -> Self {
+        PrimeGroups { rest: primes }
+    }
+}
+
+impl Iterator for PrimeGroups {
+    type Item = (BigDigit, &'static [u16]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let mut product: BigDigit = 1;
+        let mut len = 0;
+        for &p in self.rest {
+            let next = DoubleBigDigit::from(product) * DoubleBigDigit::from(p);
+            if next > DoubleBigDigit::from(big_digit::MAX) {
+                break;
+            }
+            product = next as BigDigit;
+            len += 1;
+        }
+        let (group, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Some((product, group))
+    }
+}
+
+/// Returns the smallest prime factor of `n` below 1024, if any.
+fn small_factor(n: &BigUint) -> Option<u16> {
+    for (product, group) in PrimeGroups::new(&SMALL_PRIMES) {
+        let rem = rem_digit(n, product);
+        if let Some(&p) = group.iter().find(|&&p| rem % BigDigit::from(p) == 0) {
+            return Some(p);
+        }
+    }
+    None
+}
+
+/// The number of candidates that a `Sieve` marks at a time.
+const SIEVE_WINDOW: usize = 1024;
+
+/// A sieve over the odd candidates `start`, `start + step`, `start + 2 * step`, ...,
+/// for a `step` of 2 or 4, or downward to `start - 2`, ... and so on, which skips
+/// those with an odd factor below 1024. With `safe`, it also skips candidates `p`
+/// for which `(p - 1) / 2` has such a factor.
+///
+/// Candidates are marked a window at a time, from the index of the first
+/// candidate in each window that is divisible by each small prime.
+/// Nothing is sieved if `start` is below 2048, as it could be a small prime.
+pub(crate) struct Sieve {
+    start: BigUint,
+    step: u64,
+    down: bool,
+    safe: bool,
+    /// The index of the first candidate `≡ 0` and `≡ 1` modulo each odd small prime
+    roots: Vec<(u16, u16)>,
+    /// The index of the first candidate in `window`
+    base: u64,
+    window: Vec<bool>,
+    pos: usize,
+}
+
+impl Sieve {
+    pub(crate) fn new(start: BigUint, step: u64, safe: bool) -> Self {
+        Self::with_direction(start, step, false, safe)
+    }
+
+    pub(super) fn downward(start: BigUint) -> Self {
+        Self::with_direction(start, 2, true, false)
+    }
+
+    #[allow(clippy::useless_conversion)]
+    fn with_direction(start: BigUint, step: u64, down: bool, safe: bool) -> Self {
+        debug_assert!(start.is_odd());
+        // The roots below need the inverse of `step` modulo each odd small
+        // prime, so it must not have any of them as a factor.
+        assert!(step == 2 || step == 4, "sieve step must be 2 or 4");
+        let mut roots = Vec::new();
+        if start.bits() > 11 {
+            for (product, group) in PrimeGroups::new(&SMALL_PRIMES[1..]) {
+                let rem = rem_digit(&start, product);
+                roots.extend(group.iter().map(|&p| {
+                    let p = u64::from(p);
+                    let r = u64::from(rem) % p;
+                    // candidate i is r + i * step, so the target t is at (t - r) / step
+                    let step = if down { p - step % p } else { step % p };
+                    let inv = pow_mod_u64(step, p - 2, p);
+                    let root = |t: u64| ((t + p - r) * inv % p) as u16;
+                    (root(0), root(1))
+                }));
+            }
+        }
+        Sieve {
+            start,
+            step,
+            down,
+            safe,
+            roots,
+            base: 0,
+            window: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    fn fill_window(&mut self) {
+        self.base += self.window.len() as u64;
+        self.window.clear();
+        self.window.resize(SIEVE_WINDOW, true);
+        for (&p, &(r0, r1)) in SMALL_PRIMES[1..].iter().zip(&self.roots) {
+            let p = u64::from(p);
+            let offset = self.base % p;
+            for &(r, skip) in &[(r0, true), (r1, self.safe)] {
+                if skip {
+                    let mut i = ((u64::from(r) + p - offset) % p) as usize;
+                    while i < SIEVE_WINDOW {
+                        self.window[i] = false;
+                        i += p as usize;
+                    }
+                }
+            }
+        }
+        self.pos = 0;
+    }
+}
+
+impl Iterator for Sieve {
+    type Item = BigUint;
+
+    fn next(&mut self) -> Option<BigUint> {
+        loop {
+            if self.pos == self.window.len() {
+                self.fill_window();
+            }
+            let i = self.base + self.pos as u64;
+            self.pos += 1;
+            if self.window[self.pos - 1] {
+                let delta = i * self.step;
+                return if !self.down {
+                    Some(&self.start + delta)
+                } else if self.start >= BigUint::from(delta) {
+                    Some(&self.start - delta)
+                } else {
+                    None
+                };
+            }
+        }
+    }
+}
+
+pub(super) fn pow_mod_u64(mut base: u64, mut exp: u64, m: u64) -> u64 {
+    let mul = |a: u64, b: u64| (u128::from(a) * u128::from(b) % u128::from(m)) as u64;
+    let mut acc = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            acc = mul(acc, base);
+        }
+        base = mul(base, base);
+        exp >>= 1;
+    }
+    acc
+}
+
+/// Returns the smallest prime in `[from, to)`, if any.
+#[cfg(feature = "rand")]
+pub(crate) fn first_prime(from: &BigUint, to: &BigUint) -> Option<BigUint> {
+    let two = BigUint::from(2u32);
+    if *from <= two {
+        return if two < *to { Some(two) } else { None };
+    }
+    let start = if from.is_even() {
+        from + 1u32
+    } else {
+        from.clone()
+    };
+    Sieve::new(start, 2, false)
+        .take_while(|n| n < to)
+        .find(|n| is_probably_prime(n, 0))
+}
+
+pub(super) fn next_prime(n: &BigUint) -> BigUint {
+    if *n < BigUint::from(2u32) {
+        return BigUint::from(2u32);
+    }
+    let start = if n.is_even() { n + 1u32 } else { n + 2u32 };
+    Sieve::new(start, 2, false)
+        .find(|n| is_probably_prime(n, 0))
+        .unwrap()
+}
+
+pub(super) fn prev_prime(n: &BigUint) -> Option<BigUint> {
+    if *n <= BigUint::from(3u32) {
+        return if *n == BigUint::from(3u32) {
+            Some(BigUint::from(2u32))
+        } else {
+            None
+        };
+    }
+    let start = if n.is_even() { n - 1u32 } else { n - 2u32 };
+    Sieve::downward(start).find(|n| is_probably_prime(n, 0))
+}
+
+pub(super) fn is_probably_prime(n: &BigUint, rounds: usize) -> bool {
+    if let Some(p) = small_factor(n) {
+        return *n == BigUint::from(p);
+    }
+    if let Some(n) = n.to_u64() {
+        // Any composite below 1024^2 has a factor below 1024.
+        return n > 1 && (n < 1 << 20 || is_prime_u64(n));
+    }
+
+    let mr = MillerRabin::new(n);
+    if *n < BigUint::from(MR_DETERMINISTIC_BOUND) {
+        return SMALL_PRIMES[..MR_DETERMINISTIC_BASES]
+            .iter()
+            .all(|&p| mr.is_strong_probable_prime(&BigUint::from(p)));
+    }
+
+    // Baillie–PSW: a strong probable prime test to base 2, then a Lucas test.
+    if !mr.is_strong_probable_prime(&BigUint::from(2u32))
+        || !is_extra_strong_lucas_probable_prime(&mr.ctx)
+    {
+        return false;
+    }
+
+    // Further bases are pseudo-random, but chosen deterministically from `n`.
+    let mut state = n.iter_u64_digits().next().unwrap();
+    (0..rounds).all(|_| {
+        let base = BigUint::from(splitmix64(&mut state)) + 3u32;
+        mr.is_strong_probable_prime(&base)
+    })
+}
+
+/// One step of the SplitMix64 generator.
+pub(super) fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Deterministic Miller–Rabin for an odd `n < 2^64`, larger than the bases.
+fn is_prime_u64(n: u64) -> bool {
+    let mul = |a: u64, b: u64| (u128::from(a) * u128::from(b) % u128::from(n)) as u64;
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    SMALL_PRIMES[..MR_DETERMINISTIC_BASES - 1].iter().all(|&p| {
+        let mut x = pow_mod_u64(u64::from(p), d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul(x, x);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Strong probable prime tests for a fixed odd `n`, with `n - 1 = d * 2^s`.
+pub(super) struct MillerRabin {
+    ctx: MontgomeryContext,
+    one: MontgomeryElement,
+    minus_one: MontgomeryElement,
+    d: BigUint,
+    s: u64,
+}
+
+impl MillerRabin {
+    pub(super) fn new(n: &BigUint) -> Self {
+        let ctx = MontgomeryContext::new(n);
+        let n_minus_one = n - 1u32;
+        let s = n_minus_one.trailing_zeros().unwrap();
+        MillerRabin {
+            one: ctx.one(),
+            minus_one: ctx.to_monty(&n_minus_one),
+            d: n_minus_one >> s,
+            s,
+            ctx,
+        }
+    }
+
+    /// Returns whether `n` is a strong probable prime to the given base.
+    pub(super) fn is_strong_probable_prime(&self, base: &BigUint) -> bool {
+        let base = self.ctx.to_monty(base);
+        let mut x = self.ctx.pow(&base, &self.d);
+        if x == self.one || x == self.minus_one {
+            return true;
+        }
+        for _ in 1..self.s {
+            x = self.ctx.square(&x);
+            if x == self.minus_one {
+                return true;
+            }
+            if x == self.one {
+                return false;
+            }
+        }
+        false
+    }
+}
+
+/// Extra strong Lucas probable prime test, with `Q = 1` and the first `P` in
+/// 3, 4, 5, ... such that `(D / n) = -1` for `D = P^2 - 4`.
+/// See Baillie, Fiori and Wagstaff, "Strengthening the Baillie-PSW primality test".
+///
+/// Requires an odd `n` larger than any `P + 2` tried, so a zero Jacobi symbol
+/// means that `n` has a proper factor.
+fn is_extra_strong_lucas_probable_prime(ctx: &MontgomeryContext) -> bool {
+    let n = ctx.modulus();
+
+    // A square has no such `D`.
+    let root = n.sqrt();
+    if root.square() == *n {
+        return false;
+    }
+
+    let mut p = 3u32;
+    loop {
+        let d = BigUint::from(p * p - 4);
+        match jacobi(&d, n) {
+            -1 => break,
+            // `n` is larger than `D`, so this is a proper factor.
+            0 => return false,
+            _ => p += 1,
+        }
+    }
+
+    // n + 1 = k * 2^s, with k odd
+    let n_plus_one = n + 1u32;
+    let s = n_plus_one.trailing_zeros().unwrap();
+    let k = n_plus_one >> s;
+
+    // Compute V_k and V_k+1 with a ladder from V_0 = 2 and V_1 = P, using
+    // V_2j = V_j^2 - 2 and V_2j+1 = V_j V_j+1 - P.
+    let two = ctx.to_monty(&BigUint::from(2u32));
+    let p = ctx.to_monty(&BigUint::from(p));
+    let mut v = two.clone();
+    let mut v1 = p.clone();
+    for i in (0..k.bits()).rev() {
+        if k.bit(i) {
+            v = ctx.sub(&ctx.mul(&v, &v1), &p);
+            v1 = ctx.sub(&ctx.square(&v1), &two);
+        } else {
+            v1 = ctx.sub(&ctx.mul(&v, &v1), &p);
+            v = ctx.sub(&ctx.square(&v), &two);
+        }
+    }
+
+    // U_k = 0 if and only if P V_k = 2 V_k+1, since D U_k = 2 V_k+1 - P V_k.
+    let minus_two = ctx.to_monty(&(n - 2u32));
+    if (v == two || v == minus_two) && ctx.mul(&p, &v) == ctx.add(&v1, &v1) {
+        return true;
+    }
+    for _ in 1..s {
+        if v.is_zero() {
+            return true;
+        }
+        // Once V reaches the fixed point 2, it will never be zero.
+        if v == two {
+            return false;
+        }
+        v = ctx.sub(&ctx.square(&v), &two);
+    }
+    false
+}
+
+#[test]
+fn test_small_primes() {
+    let mut sieve = vec![true; 1 << 16];
+    sieve[0] = false;
+    sieve[1] = false;
+    for i in 2..sieve.len() {
+        if sieve[i] {
+            for j in (i * i..sieve.len()).step_by(i) {
+                sieve[j] = false;
+            }
+        }
+    }
+    let primes = (0..1024).filter(|&i| sieve[i]);
+    assert!(primes.eq(SMALL_PRIMES.iter().map(|&p| usize::from(p))));
+
+    for (i, &is_prime) in sieve.iter().enumerate() {
+        assert_eq!(is_probably_prime(&BigUint::from(i), 0), is_prime, "{}", i);
+    }
+}
+
+#[test]
+fn test_next_prev_prime() {
+    let primes: Vec<u32> = (0u32..5000)
+        .filter(|&i| is_probably_prime(&BigUint::from(i), 0))
+        .collect();
+    for i in 0u32..4900 {
+        let next = primes.iter().find(|&&p| p > i).unwrap();
+        let prev = primes.iter().rev().find(|&&p| p < i);
+        let n = BigUint::from(i);
+        assert_eq!(next_prime(&n), BigUint::from(*next), "{}", i);
+        assert_eq!(prev_prime(&n), prev.map(|&p| BigUint::from(p)), "{}", i);
+    }
+}
+
+#[test]
+fn test_sieve() {
+    let no_small_factor = |n: &BigUint| small_factor(n).is_none();
+    for &start in &[2049u64, 100_001, 1 << 40 | 1, (1 << 62) + 3] {
+        let start = BigUint::from(start);
+        let sieved: Vec<_> = Sieve::new(start.clone(), 2, false).take(3000).collect();
+        let expected: Vec<_> = (0u32..)
+            .map(|i| &start + 2 * i)
+            .filter(no_small_factor)
+            .take(3000)
+            .collect();
+        assert_eq!(sieved, expected);
+
+        let sieved: Vec<_> = Sieve::downward(start.clone()).take(3000).collect();
+        let expected: Vec<_> = (0u32..)
+            .map(|i| 2 * i)
+            .take_while(|&d| start >= BigUint::from(d))
+            .map(|d| &start - d)
+            .filter(no_small_factor)
+            .take(3000)
+            .collect();
+        assert_eq!(sieved, expected);
+
+        let start = &start | BigUint::from(3u32);
+        let sieved: Vec<_> = Sieve::new(start.clone(), 4, true).take(1000).collect();
+        let expected: Vec<_> = (0u32..)
+            .map(|i| &start + 4 * i)
+            .filter(|n| no_small_factor(n) && no_small_factor(&(n >> 1u8)))
+            .take(1000)
+            .collect();
+        assert_eq!(sieved, expected);
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_sieve_step() {
+    // 3 divides the step, so it would have no inverse for the roots.
+    Sieve::new(BigUint::from(4097u32), 6, false);
+}
+
+#[test]
+fn test_extra_strong_lucas() {
+    // The extra strong Lucas pseudoprimes below 40000.
+    let pseudoprimes = [989u32, 3239, 5777, 10877, 27971, 29681, 30739, 31631, 39059];
+    for n in (1025u32..40000).step_by(2) {
+        let big = BigUint::from(n);
+        if big.sqrt().square() == big {
+            continue;
+        }
+        let prime = is_prime_u64(u64::from(n));
+        let lucas = is_extra_strong_lucas_probable_prime(&MontgomeryContext::new(&big));
+        assert_eq!(lucas, prime || pseudoprimes.contains(&n), "{}", n);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::big_digit::BigDigit;
+use crate::std_alloc::Vec;
+
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+use core::sync::atomic::{self, Ordering};
+
+/// Overwrites `data` with zeros, in a way that the compiler won't optimize
+/// away, even if the memory is about to be freed.
+pub(super) fn wipe<T: Copy + Default>(data: &mut [T]) {
+    for x in data.iter_mut() {
+        // It's safe to write through a mutable reference; volatile just keeps
+        // the write from being elided.
+        unsafe { ptr::write_volatile(x, T::default()) };
+    }
+    atomic::compiler_fence(Ordering::SeqCst);
+}
+
+/// Digits of a secret value, which are wiped when dropped.
+///
+/// The length is fixed at creation, so the digits are never reallocated and
+/// can't leave copies behind in freed memory.
+pub(super) struct SecretDigits(Vec<BigDigit>);
+
+impl SecretDigits {
+    pub(super) fn zeroed(len: usize) -> Self {
+        SecretDigits(vec![0; len])
+    }
+
+    /// Returns a copy of `x` zero-extended to `len` digits.
+    pub(super) fn padded(x: &[BigDigit], len: usize) -> Self {
+        let mut z = SecretDigits::zeroed(len);
+        z[..x.len()].copy_from_slice(x);
+        z
+    }
+}
+
+impl Clone for SecretDigits {
+    fn clone(&self) -> Self {
+        SecretDigits::padded(&self.0, self.0.len())
+    }
+}
+
+impl Drop for SecretDigits {
+    fn drop(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+impl Deref for SecretDigits {
+    type Target = [BigDigit];
+
+    fn deref(&self) -> &[BigDigit] {
+        &self.0
+    }
+}
+
+impl DerefMut for SecretDigits {
+    fn deref_mut(&mut self) -> &mut [BigDigit] {
+        &mut self.0
+    }
+}
+
+#[test]
+fn test_wipe() {
+    let mut data = [1u8, 2, 3, 255];
+    wipe(&mut data);
+    assert_eq!(data, [0; 4]);
+
+    let x = SecretDigits::padded(&[1, 2], 3);
+    assert_eq!(&*x, &[1, 2, 0]);
+    assert_eq!(&*x.clone(), &[1, 2, 0]);
+}
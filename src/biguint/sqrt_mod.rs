@@ -0,0 +1,160 @@
+use super::gcd::{jacobi, modinv};
+use super::perfect_power::is_perfect_square;
+use super::BigUint;
+
+use core::cmp;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+/// Computes a square root of `a` modulo an odd prime `p`, which is the smaller
+/// of the two roots `x` and `p - x`, or `None` if `a` is not a quadratic residue.
+pub(super) fn sqrt_mod_prime(a: &BigUint, p: &BigUint) -> Option<BigUint> {
+    assert!(!p.is_zero(), "attempt to calculate with zero modulus!");
+    let a = a % p;
+    if a.is_zero() || p.is_even() {
+        // Every value is its own square root modulo 2.
+        return Some(a);
+    }
+
+    let p8 = p.data[0] & 7;
+    let x = if p8 & 3 == 3 {
+        // x = a^((p + 1) / 4)
+        a.modpow(&((p >> 2) + 1u32), p)
+    } else if p8 == 5 {
+        // Atkin's algorithm: with v = (2a)^((p - 5) / 8) and i = 2a v^2,
+        // which is a square root of -1, x = a v (i - 1).
+        let two_a = (&a << 1u8) % p;
+        let v = two_a.modpow(&(p >> 3), p);
+        let i = two_a * v.square() % p;
+        let i_minus_one = if i.is_zero() { p - 1u32 } else { i - 1u32 };
+        a.clone() * v % p * i_minus_one % p
+    } else {
+        tonelli_shanks(&a, p)?
+    };
+
+    // This also rejects the non-residues in the special cases.
+    if x.square() % p != a {
+        return None;
+    }
+    let y = p - &x;
+    Some(if y < x { y } else { x })
+}
+
+/// The Tonelli–Shanks algorithm, for `p = 1 (mod 8)`.
+fn tonelli_shanks(a: &BigUint, p: &BigUint) -> Option<BigUint> {
+    if jacobi(a, p) != 1 {
+        return None;
+    }
+
+    // p - 1 = q * 2^s, with q odd
+    let p_minus_one = p - 1u32;
+    let s = p_minus_one.trailing_zeros().unwrap();
+    let q = &p_minus_one >> s;
+
+    // Find a non-residue z, which is quick on average. There is none for a
+    // square `p`, but that isn't prime anyway, while every other `p` has one
+    // below it.
+    if is_perfect_square(p) {
+        return None;
+    }
+    let mut z = BigUint::from(2u32);
+    while jacobi(&z, p) != -1 {
+        z += 1u32;
+    }
+
+    let mut m = s;
+    let mut c = z.modpow(&q, p);
+    let mut t = a.modpow(&q, p);
+    let mut x = a.modpow(&((q + 1u32) >> 1), p);
+    while !t.is_one() {
+        // Find the least i with t^(2^i) = 1, which is less than m for a residue.
+        let mut i = 0;
+        let mut t2i = t.clone();
+        while !t2i.is_one() {
+            t2i = t2i.square() % p;
+            i += 1;
+            if i == m {
+                return None;
+            }
+        }
+
+        let mut b = c;
+        for _ in 0..m - i - 1 {
+            b = b.square() % p;
+        }
+        m = i;
+        c = b.square() % p;
+        t = t * &c % p;
+        x = x * b % p;
+    }
+    Some(x)
+}
+
+/// Computes a square root of `a` modulo `p^k` for a prime `p`, or `None` if
+/// there is none, by lifting a root modulo `p` with Hensel's lemma.
+pub(super) fn sqrt_mod_prime_power(a: &BigUint, p: &BigUint, k: u32) -> Option<BigUint> {
+    assert!(!p.is_zero(), "attempt to calculate with zero modulus!");
+    let modulus = p.pow(k);
+    let a = a % &modulus;
+    if a.is_zero() || k == 0 {
+        return Some(BigUint::zero());
+    }
+
+    // With a = p^e b for b coprime to p, the square root is p^(e/2) times
+    // a square root of b modulo p^(k - e).
+    let mut e = 0;
+    let mut b = a;
+    loop {
+        let (q, r) = b.div_rem(p);
+        if !r.is_zero() {
+            break;
+        }
+        b = q;
+        e += 1;
+    }
+    if e & 1 == 1 {
+        return None;
+    }
+
+    let root = if p.is_even() {
+        sqrt_mod_pow2(&b, k - e)?
+    } else {
+        sqrt_mod_odd_prime_power(&b, p, k - e)?
+    };
+    Some(root * p.pow(e / 2) % modulus)
+}
+
+/// Lifts a square root of `a` modulo an odd prime `p` to one modulo `p^k`,
+/// doubling the precision at each step with Newton's iteration
+/// `x = x - (x^2 - a) / (2x)`. Requires `a` coprime to `p`.
+fn sqrt_mod_odd_prime_power(a: &BigUint, p: &BigUint, k: u32) -> Option<BigUint> {
+    let mut x = sqrt_mod_prime(a, p)?;
+    let mut j = 1;
+    while j < k {
+        j = cmp::min(2 * j, k);
+        let pj = p.pow(j);
+        let fx = (x.square() + &pj - a % &pj) % &pj;
+        let inv = modinv(&(&x << 1u8), &pj)?;
+        x = (&x + &pj - fx * inv % &pj) % &pj;
+    }
+    Some(x)
+}
+
+/// Computes a square root of an odd `a` modulo `2^k`, fixing one bit at a time.
+fn sqrt_mod_pow2(a: &BigUint, k: u32) -> Option<BigUint> {
+    // Odd squares are 1 modulo 8, and this is all that's needed modulo 2^k.
+    let mask = (1 << cmp::min(k, 3)) - 1;
+    if a.data[0] & mask != 1 {
+        return None;
+    }
+
+    // If x^2 = a (mod 2^j), then either x or x + 2^(j-1) is a root modulo 2^(j+1).
+    let mut x = BigUint::one();
+    for j in 3..k {
+        let modulus = BigUint::one() << (j + 1);
+        if (x.square() % &modulus) != a % &modulus {
+            x.set_bit(u64::from(j - 1), true);
+        }
+    }
+    Some(x)
+}
@@ -227,7 +227,15 @@ impl<T> fmt::Display for TryFromBigIntError<T> {
     }
 }
 
+pub use crate::biguint::BarrettReducer;
 pub use crate::biguint::BigUint;
+pub use crate::biguint::ConstantTimeContext;
+pub use crate::biguint::CrtBasis;
+pub use crate::biguint::Factorization;
+pub use crate::biguint::Factorizer;
+pub use crate::biguint::FixedUint;
+pub use crate::biguint::MontgomeryContext;
+pub use crate::biguint::MontgomeryElement;
 pub use crate::biguint::ToBigUint;
 pub use crate::biguint::U32Digits;
 pub use crate::biguint::U64Digits;
@@ -966,6 +966,19 @@ fn test_checked_mul() {
         assert!(a == c.checked_mul(&b).unwrap() + &d);
     }
 }
+#[test]
+fn test_square() {
+    for elm in MUL_TRIPLES.iter() {
+        let (a_vec, _, _) = *elm;
+        let a = BigInt::from_slice(Plus, a_vec);
+        let b = a.clone();
+        let c = &a * &b;
+
+        assert_eq!(a.square(), c);
+        assert_eq!((-&a).square(), c);
+    }
+}
+
 #[test]
 fn test_checked_div() {
     for elm in MUL_TRIPLES.iter() {
@@ -1013,6 +1026,112 @@ fn test_gcd() {
     check(-4, -2, 2);
 }
 
+#[test]
+fn test_extended_gcd() {
+    fn check(a: &BigInt, b: &BigInt) {
+        let egcd = a.extended_gcd(b);
+        assert_eq!(egcd.gcd, a.gcd(b));
+        assert_eq!(a * egcd.x + b * egcd.y, egcd.gcd);
+    }
+
+    for a in -20i32..21 {
+        for b in -20i32..21 {
+            check(&BigInt::from(a), &BigInt::from(b));
+        }
+    }
+
+    // large enough to take multi-digit Lehmer steps
+    let three = BigInt::from(3);
+    let five = BigInt::from(5);
+    let seven = BigInt::from(7);
+    let a = (&three).pow(500u32) * (&seven).pow(100u32);
+    let b = (&five).pow(300u32) * (&seven).pow(120u32);
+    check(&a, &b);
+    check(&-&a, &b);
+    check(&b, &-&a);
+    check(&(&a + 1), &b);
+    check(&a, &BigInt::from(1u64 << 40));
+    assert_eq!(a.extended_gcd(&b).gcd, (&seven).pow(100u32));
+}
+
+#[test]
+fn test_jacobi() {
+    // Euler's criterion for odd primes
+    for &p in &[3i64, 5, 7, 11, 101, 65537] {
+        let big_p = BigInt::from(p);
+        for a in -300i64..300 {
+            let euler = BigInt::from(a).modpow(&BigInt::from((p - 1) / 2), &big_p);
+            let expected = if euler.is_zero() {
+                0
+            } else if euler.is_one() {
+                1
+            } else {
+                -1
+            };
+            assert_eq!(BigInt::from(a).jacobi(&big_p), expected);
+            assert_eq!(BigInt::from(a).legendre(&big_p), expected);
+        }
+    }
+
+    // multiplicative in the modulus
+    let p = BigInt::from(2u32).pow(127u32) - 1;
+    let q = BigInt::from(2u32).pow(89u32) - 1;
+    let pq = &p * &q;
+    for a in 0i64..100 {
+        let a = BigInt::from(a * 1000003 - 7);
+        assert_eq!(a.jacobi(&pq), a.jacobi(&p) * a.jacobi(&q));
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_jacobi_even() {
+    BigInt::from(3).jacobi(&BigInt::from(8));
+}
+
+#[test]
+fn test_kronecker() {
+    fn naive(a: i64, n: i64) -> i8 {
+        if n == 0 {
+            return if a == 1 || a == -1 { 1 } else { 0 };
+        }
+        let mut k = if n < 0 && a < 0 { -1 } else { 1 };
+        let mut n = n.abs();
+        let mut p = 2;
+        while n > 1 {
+            while n % p == 0 {
+                n /= p;
+                k *= if p == 2 {
+                    match a & 7 {
+                        1 | 7 => 1,
+                        3 | 5 => -1,
+                        _ => 0,
+                    }
+                } else {
+                    let a = BigInt::from(a);
+                    let euler = a.modpow(&BigInt::from((p - 1) / 2), &BigInt::from(p));
+                    if euler.is_zero() {
+                        0
+                    } else if euler.is_one() {
+                        1
+                    } else {
+                        -1
+                    }
+                };
+            }
+            p += 1;
+        }
+        k
+    }
+
+    for a in -40i64..40 {
+        for n in -40i64..40 {
+            let k = BigInt::from(a).kronecker(&BigInt::from(n));
+            assert_eq!(k, naive(a, n), "({} / {})", a, n);
+        }
+    }
+}
+
 #[test]
 fn test_lcm() {
     fn check(a: isize, b: isize, c: isize) {
@@ -1404,3 +1523,23 @@ fn test_set_bit() {
     x.set_bit(0, false);
     assert_eq!(x, BigInt::from_biguint(Minus, BigUint::one() << 200));
 }
+
+#[test]
+fn test_ilog() {
+    let x = BigInt::from(10).pow(50u32);
+    assert_eq!(x.ilog10(), 50);
+    assert_eq!(x.ilog2(), 166);
+    assert_eq!(x.ilog(&BigInt::from(100)), 25);
+
+    assert_eq!((-&x).checked_ilog10(), None);
+    assert_eq!((-&x).checked_ilog2(), None);
+    assert_eq!(BigInt::zero().checked_ilog10(), None);
+    assert_eq!(x.checked_ilog(&BigInt::from(-10)), None);
+    assert_eq!(x.checked_ilog(&BigInt::one()), None);
+}
+
+#[test]
+#[should_panic]
+fn test_ilog_negative() {
+    BigInt::from(-100).ilog10();
+}
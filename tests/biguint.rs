@@ -897,6 +897,53 @@ fn test_div_rem() {
     }
 }
 
+#[test]
+fn test_div_rem_big() {
+    fn check(a: &BigUint, b: &BigUint) {
+        let (q, r) = a.div_rem(b);
+        assert!(r < *b);
+        assert_eq!(&q * b + &r, *a);
+        assert_eq!(a / b, q);
+        assert_eq!(a % b, r);
+    }
+
+    // Cover both the base and the recursive division algorithms, with quotients
+    // that are shorter, as long as, and longer than the divisor.
+    let mut digit = 1u32;
+    let mut digits = |n: usize| -> BigUint {
+        let digits: Vec<u32> = (0..n)
+            .map(|_| {
+                digit = digit.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                digit
+            })
+            .collect();
+        BigUint::new(digits)
+    };
+    for &(b_len, q_len) in &[
+        (50, 50),
+        (129, 131),
+        (300, 300),
+        (300, 1000),
+        (1000, 20),
+        (1000, 300),
+    ] {
+        let a = digits(b_len + q_len);
+        let b = digits(b_len);
+        check(&a, &b);
+        check(&a, &(&b >> 17u32));
+
+        // Dividends and divisors with runs of ones make for the hardest quotient estimates.
+        let ones = BigUint::new(vec![!0; b_len + q_len]);
+        let all_ones = BigUint::new(vec![!0; b_len]);
+        let top_bit = (BigUint::one() << (32 * b_len - 1)) + 1u32;
+        check(&ones, &b);
+        check(&ones, &all_ones);
+        check(&ones, &top_bit);
+        check(&(&all_ones * &all_ones), &all_ones);
+        check(&(&all_ones * &all_ones - 1u32), &all_ones);
+    }
+}
+
 #[test]
 fn test_div_rem_big_multiple() {
     let a = BigUint::from(3u32).pow(100u32);
@@ -1028,6 +1075,35 @@ fn test_mul_overflow_2() {
     }
 }
 
+#[test]
+fn test_square() {
+    for elm in MUL_TRIPLES.iter() {
+        let (a_vec, _, _) = *elm;
+        let a = BigUint::from_slice(a_vec);
+        let b = a.clone();
+        assert_eq!(a.square(), &a * &b);
+    }
+
+    // Cover each of the squaring algorithms, with and without carries.
+    let mut digit = 1u32;
+    for &len in &[1, 2, 31, 33, 64, 100, 257, 300, 1000, 3000, 9000] {
+        let digits: Vec<u32> = (0..len)
+            .map(|_| {
+                digit = digit.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                digit
+            })
+            .collect();
+        let a = BigUint::new(digits);
+        let b = a.clone();
+        assert_eq!(a.square(), &a * &b);
+        assert_eq!((&a << 100u32).square(), (&a * &b) << 200u32);
+
+        let ones = BigUint::new(vec![!0; len]);
+        let ones2 = ones.clone();
+        assert_eq!(ones.square(), &ones * &ones2);
+    }
+}
+
 #[test]
 fn test_checked_div() {
     for elm in MUL_TRIPLES.iter() {
@@ -1065,6 +1141,37 @@ fn test_gcd() {
     check(56, 42, 14);
 }
 
+#[test]
+fn test_gcd_big() {
+    fn check(a: &BigUint, b: &BigUint, c: &BigUint) {
+        assert_eq!(&a.gcd(b), c);
+        assert_eq!(&b.gcd(a), c);
+
+        let (gcd, lcm) = a.gcd_lcm(b);
+        assert_eq!(&gcd, c);
+        assert_eq!(lcm, a.lcm(b));
+        assert_eq!(gcd * lcm, a * b);
+    }
+
+    // gcd(F(m), F(n)) = F(gcd(m, n)), with all the Euclidean quotients small
+    let mut fib = vec![BigUint::zero(), BigUint::one()];
+    for i in 2..6001 {
+        let next = &fib[i - 1] + &fib[i - 2];
+        fib.push(next);
+    }
+    check(&fib[6000], &fib[4500], &fib[1500]);
+    check(&fib[6000], &fib[5999], &BigUint::one());
+    check(&fib[5000], &fib[3000], &fib[1000]);
+
+    let g = BigUint::from(3u32).pow(8000u32) * BigUint::from(11u32).pow(2000u32);
+    let a = BigUint::from(5u32).pow(12000u32);
+    let b = BigUint::from(7u32).pow(10000u32);
+    check(&(&g * &a), &(&g * &b), &g);
+    check(&(&g * &a), &(&g * &b * &b), &g);
+    check(&(&g * &a * &b), &g, &g);
+    check(&(&a << 1000), &(&b << 1234), &(BigUint::one() << 1000));
+}
+
 #[test]
 fn test_lcm() {
     fn check(a: usize, b: usize, c: usize) {
@@ -1624,6 +1731,38 @@ fn test_big_str() {
     }
 }
 
+#[test]
+fn test_big_str_radix() {
+    // Long runs of zeros or maximal digits, in every radix, are the edge cases
+    // for splitting the conversion in halves.
+    for radix in 2..37 {
+        let digit = std::char::from_digit(radix - 1, radix).unwrap();
+        for &len in &[100, 1000, 5000] {
+            let x = BigUint::from(radix).pow(len as u32);
+            let s = format!("1{}", "0".repeat(len));
+            assert_eq!(x.to_str_radix(radix), s);
+            assert_eq!(BigUint::from_str_radix(&s, radix).unwrap(), x);
+
+            let x = x - 1u32;
+            let s = digit.to_string().repeat(len);
+            assert_eq!(x.to_str_radix(radix), s);
+            assert_eq!(BigUint::from_str_radix(&s, radix).unwrap(), x);
+        }
+    }
+
+    for &radix in &[100, 255] {
+        let x = BigUint::from(radix).pow(5000u32) + 1u32;
+        let le = x.to_radix_le(radix);
+        assert_eq!(le.len(), 5001);
+        assert_eq!((le[0], le[5000]), (1, 1));
+        assert!(le[1..5000].iter().all(|&d| d == 0));
+        assert_eq!(BigUint::from_radix_le(&le, radix).unwrap(), x);
+
+        let be = x.to_radix_be(radix);
+        assert_eq!(BigUint::from_radix_be(&be, radix).unwrap(), x);
+    }
+}
+
 #[test]
 fn test_lower_hex() {
     let a = BigUint::parse_bytes(b"A", 16).unwrap();
@@ -1862,3 +2001,219 @@ fn test_set_bit() {
     x.set_bit(1, false);
     assert_eq!(x, BigUint::zero());
 }
+
+#[test]
+fn test_factorial() {
+    let mut expected = BigUint::one();
+    for n in 0u64..600 {
+        if n > 0 {
+            expected *= n;
+        }
+        assert_eq!(BigUint::factorial(n), expected);
+    }
+    // 1000! has 2568 digits, and ends with 249 zeros
+    let f = BigUint::factorial(1000).to_string();
+    assert_eq!(f.len(), 2568);
+    assert!(f.starts_with("402387260077"));
+    assert_eq!(f.len() - f.trim_end_matches('0').len(), 249);
+}
+
+#[test]
+fn test_double_factorial() {
+    let expected = [1u32, 1, 2, 3, 8, 15, 48, 105, 384, 945, 3840, 10395];
+    for (n, &x) in expected.iter().enumerate() {
+        assert_eq!(BigUint::double_factorial(n as u64), BigUint::from(x));
+    }
+    for n in 1u64..300 {
+        let product = BigUint::double_factorial(n) * BigUint::double_factorial(n - 1);
+        assert_eq!(product, BigUint::factorial(n));
+    }
+}
+
+#[test]
+fn test_binomial() {
+    // Pascal's triangle
+    let mut row = vec![BigUint::one()];
+    for n in 1u64..300 {
+        let mut next = vec![BigUint::one()];
+        next.extend(row.windows(2).map(|w| &w[0] + &w[1]));
+        next.push(BigUint::one());
+        row = next;
+        for (k, x) in row.iter().enumerate() {
+            assert_eq!(BigUint::binomial(n, k as u64), *x);
+        }
+        assert_eq!(BigUint::binomial(n, n + 1), BigUint::zero());
+    }
+
+    let n = 1u64 << 40;
+    let expected = BigUint::from(n) * (n - 1) * (n - 2) / 6u32;
+    assert_eq!(BigUint::binomial(n, 3), expected);
+    assert_eq!(BigUint::binomial(n, n - 3), expected);
+    assert_eq!(
+        BigUint::binomial(10000, 5000),
+        BigUint::factorial(10000) / BigUint::factorial(5000).pow(2u32)
+    );
+}
+
+#[test]
+fn test_multinomial() {
+    assert_eq!(BigUint::multinomial(&[]), BigUint::one());
+    assert_eq!(BigUint::multinomial(&[7]), BigUint::one());
+    // MISSISSIPPI
+    assert_eq!(BigUint::multinomial(&[1, 4, 4, 2]), BigUint::from(34650u32));
+    for n in 0u64..50 {
+        for k in 0..=n {
+            assert_eq!(BigUint::multinomial(&[k, n - k]), BigUint::binomial(n, k));
+        }
+    }
+    let ks = [100, 200, 0, 300, 1];
+    let denominator: BigUint = ks.iter().map(|&k| BigUint::factorial(k)).product();
+    assert_eq!(
+        BigUint::multinomial(&ks),
+        BigUint::factorial(601) / denominator
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_multinomial_overflow() {
+    BigUint::multinomial(&[1 << 63, 1 << 63]);
+}
+
+#[test]
+fn test_primorial() {
+    let expected = [1u32, 1, 2, 6, 6, 30, 30, 210, 210, 210, 210, 2310];
+    for (n, &x) in expected.iter().enumerate() {
+        assert_eq!(BigUint::primorial(n as u64), BigUint::from(x));
+    }
+    let mut p = BigUint::one();
+    for n in 2u64..2000 {
+        if BigUint::from(n).is_probably_prime(0) {
+            p *= n;
+        }
+        assert_eq!(BigUint::primorial(n), p);
+    }
+}
+
+#[test]
+fn test_fibonacci() {
+    let (mut a, mut b) = (BigUint::zero(), BigUint::one());
+    for n in 0u64..1000 {
+        assert_eq!(BigUint::fibonacci(n), a);
+        assert_eq!(BigUint::fibonacci_pair(n), (a.clone(), b.clone()));
+        // L(n) = F(n - 1) + F(n + 1) = 2 F(n + 1) - F(n)
+        assert_eq!(BigUint::lucas(n), (&b << 1u8) - &a);
+        let next = &a + &b;
+        a = b;
+        b = next;
+    }
+
+    // F(2n) = F(n) L(n), and gcd(F(m), F(n)) = F(gcd(m, n))
+    for &n in &[1000u64, 4095, 4096, 100_000] {
+        assert_eq!(
+            BigUint::fibonacci(2 * n),
+            BigUint::fibonacci(n) * BigUint::lucas(n)
+        );
+    }
+    assert_eq!(
+        BigUint::fibonacci(3000).gcd(&BigUint::fibonacci(4200)),
+        BigUint::fibonacci(600)
+    );
+}
+
+#[test]
+fn test_lucas_sequence() {
+    let big = |x: u64| BigUint::from(x);
+    for m in 1u64..16 {
+        for p in 0..m {
+            for q in 0..m {
+                let (mut u, mut u_next) = (0, 1 % m);
+                let (mut v, mut v_next) = (2 % m, p);
+                for n in 0u64..20 {
+                    let expected = (big(u), big(v));
+                    assert_eq!(
+                        BigUint::lucas_sequence(&big(p), &big(q), &big(n), &big(m)),
+                        expected
+                    );
+                    let next = (p * u_next + (m - q) * u) % m;
+                    u = u_next;
+                    u_next = next;
+                    let next = (p * v_next + (m - q) * v) % m;
+                    v = v_next;
+                    v_next = next;
+                }
+            }
+        }
+    }
+
+    // With p = 1 and q = -1, these are the Fibonacci and Lucas numbers.
+    let m = (BigUint::one() << 200u32) + 1u32;
+    let q = &m - 1u32;
+    let n = 5000u64;
+    assert_eq!(
+        BigUint::lucas_sequence(&BigUint::one(), &q, &big(n), &m),
+        (BigUint::fibonacci(n) % &m, BigUint::lucas(n) % &m)
+    );
+
+    // U(p + 1) = 0 and V(p + 1) = 2q modulo a prime p, when the discriminant
+    // is a non-residue, as it is for p = 3, q = 1 and 2^127 - 1.
+    let prime = (BigUint::one() << 127u32) - 1u32;
+    let (u, v) = BigUint::lucas_sequence(&big(3), &big(1), &(&prime + 1u32), &prime);
+    assert!(u.is_zero());
+    assert_eq!(v, big(2));
+}
+
+#[test]
+fn test_ilog() {
+    let big = |x: u64| BigUint::from(x);
+    for base in 2u64..40 {
+        let mut power = 1u64;
+        let mut e = 0;
+        for x in 1u64..2000 {
+            if x >= power * base {
+                power *= base;
+                e += 1;
+            }
+            assert_eq!(big(x).checked_ilog(&big(base)), Some(e));
+        }
+    }
+
+    for &k in &[1u32, 19, 20, 100, 1000, 5000] {
+        let power = BigUint::from(10u32).pow(k);
+        assert_eq!(power.ilog10(), u64::from(k));
+        assert_eq!((&power - 1u32).ilog10(), u64::from(k) - 1);
+        assert_eq!((&power + 1u32).ilog10(), u64::from(k));
+        assert_eq!(power.to_string().len() as u64, power.ilog10() + 1);
+
+        let base = BigUint::from(3u32);
+        let power = BigUint::pow(&base, k);
+        assert_eq!(power.ilog(&base), u64::from(k));
+        assert_eq!((&power - 1u32).ilog(&base), u64::from(k) - 1);
+
+        assert_eq!(power.ilog(&power), 1);
+        assert_eq!(BigUint::pow(&power, 3).ilog(&power), 3);
+        assert_eq!((BigUint::pow(&power, 3) - 1u32).ilog(&power), 2);
+    }
+
+    let x = (BigUint::one() << 1000u32) - 1u32;
+    assert_eq!(x.ilog2(), 999);
+    assert_eq!(x.ilog(&big(32)), 199);
+    assert_eq!(x.ilog(&big(1 << 10)), 99);
+
+    assert_eq!(BigUint::zero().checked_ilog2(), None);
+    assert_eq!(BigUint::zero().checked_ilog10(), None);
+    assert_eq!(big(7).checked_ilog(&big(0)), None);
+    assert_eq!(big(7).checked_ilog(&big(1)), None);
+}
+
+#[test]
+#[should_panic]
+fn test_ilog_zero() {
+    BigUint::zero().ilog10();
+}
+
+#[test]
+#[should_panic]
+fn test_ilog_base_one() {
+    BigUint::from(7u32).ilog(&BigUint::one());
+}
@@ -53,9 +53,12 @@ static BIG_R: &str = "\
                       109c4735_6e7db425_7b5d74c7_0b709508";
 
 mod biguint {
-    use num_bigint::BigUint;
+    use num_bigint::{
+        BarrettReducer, BigUint, ConstantTimeContext, CrtBasis, FixedUint, MontgomeryContext,
+    };
     use num_integer::Integer;
-    use num_traits::Num;
+    use num_traits::{Num, One, Zero};
+    use std::cmp;
 
     fn check_modpow<T: Into<BigUint>>(b: T, e: T, m: T, r: T) {
         let b: BigUint = b.into();
@@ -106,6 +109,577 @@ mod biguint {
         assert!(even_modpow < even_m);
         assert_eq!(even_modpow % m, r);
     }
+
+    #[test]
+    fn test_modpow_even() {
+        let b = BigUint::from_str_radix(super::BIG_B, 16).unwrap();
+        let m = BigUint::from_str_radix(super::BIG_M, 16).unwrap();
+        let even_b = &b << 1u8;
+
+        for &shift in &[1u32, 31, 32, 63, 64, 65, 100, 2048] {
+            let pow2 = BigUint::one() << shift;
+            for modulus in &[&m << shift, pow2.clone(), &pow2 * 3u32] {
+                for e in 0u32..20 {
+                    let expected = b.pow(e) % modulus;
+                    assert_eq!(b.modpow(&BigUint::from(e), modulus), expected);
+                    let expected = even_b.pow(e) % modulus;
+                    assert_eq!(even_b.modpow(&BigUint::from(e), modulus), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sqrt_mod_prime_small() {
+        let primes = [
+            2u64, 3, 5, 7, 11, 13, 17, 29, 37, 41, 97, 113, 193, 241, 257, 65537,
+        ];
+        for &p in &primes {
+            let squares: Vec<u64> = (0..p).map(|x| x * x % p).collect();
+            for a in 0..cmp::min(2 * p, 1000) {
+                let root = BigUint::from(a).sqrt_mod_prime(&BigUint::from(p));
+                match root {
+                    Some(x) => {
+                        assert!(x <= BigUint::from(p / 2));
+                        assert_eq!(x.pow(2) % p, BigUint::from(a % p));
+                    }
+                    None => assert!(!squares.contains(&(a % p)), "{} mod {}", a, p),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sqrt_mod_prime_square_modulus() {
+        // Square moduli that are 1 mod 8 have no non-residue to search for.
+        for &m in &[9u32, 25, 49, 81, 121, 225] {
+            assert_eq!(BigUint::one().sqrt_mod_prime(&BigUint::from(m)), None);
+        }
+    }
+
+    #[test]
+    fn test_sqrt_mod_prime_big() {
+        let one = BigUint::one();
+        let primes = [
+            // 3 mod 4
+            BigUint::from_str_radix(super::BIG_M, 16).unwrap(),
+            // 5 mod 8
+            (&one << 255u8) - 19u32,
+            // 1 mod 2^96
+            (&one << 224u8) - (&one << 96u8) + 1u32,
+        ];
+        let b = BigUint::from_str_radix(super::BIG_B, 16).unwrap();
+        for p in &primes {
+            let mut z = BigUint::from(2u32);
+            while z.sqrt_mod_prime(p).is_some() {
+                z += 1u32;
+            }
+            for i in 0u32..3 {
+                let x = (&b + i) % p;
+                let a = x.pow(2) % p;
+                let root = a.sqrt_mod_prime(p).unwrap();
+                assert!(root == x || root == p - &x);
+
+                // a non-residue times a residue is a non-residue
+                assert_eq!((a * &z % p).sqrt_mod_prime(p), None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sqrt_mod_prime_power() {
+        for &(p, max_k) in &[(2u64, 10), (3, 6), (5, 4), (7, 4), (13, 3), (17, 3)] {
+            for k in 0..=max_k {
+                let m = p.pow(k);
+                let squares: Vec<u64> = (0..m).map(|x| x * x % m).collect();
+                for a in 0..m {
+                    let root = BigUint::from(a).sqrt_mod_prime_power(&BigUint::from(p), k);
+                    match root {
+                        Some(x) => {
+                            assert!(x < BigUint::from(m));
+                            assert_eq!(x.pow(2) % m, BigUint::from(a), "{} mod {}^{}", a, p, k);
+                        }
+                        None => assert!(!squares.contains(&a), "{} mod {}^{}", a, p, k),
+                    }
+                }
+            }
+        }
+
+        let p = (BigUint::one() << 127u8) - 1u32;
+        let b = BigUint::from_str_radix(super::BIG_B, 16).unwrap();
+        let m = p.pow(20);
+        let a = b.pow(2) % &m;
+        let root = a.sqrt_mod_prime_power(&p, 20).unwrap();
+        assert_eq!(root.pow(2) % &m, a);
+        let a = (&a * &p).pow(2) % &m;
+        let root = a.sqrt_mod_prime_power(&p, 20).unwrap();
+        assert_eq!(root.pow(2) % &m, a);
+        assert_eq!((a * &p).sqrt_mod_prime_power(&p, 20), None);
+    }
+
+    #[test]
+    fn test_multi_modpow() {
+        let b = BigUint::from_str_radix(super::BIG_B, 16).unwrap();
+        let e = BigUint::from_str_radix(super::BIG_E, 16).unwrap();
+        let m = BigUint::from_str_radix(super::BIG_M, 16).unwrap();
+
+        for modulus in &[
+            m.clone(),
+            &m << 1u8,
+            BigUint::from(1u32),
+            BigUint::from(8u32),
+        ] {
+            let mut pairs = Vec::new();
+            let mut expected = BigUint::one() % modulus;
+            assert_eq!(BigUint::multi_modpow(&pairs, modulus), expected);
+
+            for i in 0u32..40 {
+                let x = &b * (i + 1) + i;
+                let y = (&e >> (1500 + i * 53 % 500)) * i;
+                expected = expected * x.modpow(&y, modulus) % modulus;
+                pairs.push((x, y));
+                if i < 4 || i == 39 {
+                    assert_eq!(BigUint::multi_modpow(&pairs, modulus), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_modpow_small() {
+        for m in 1u64..20 {
+            for a in 0u64..10 {
+                for b in 0u64..10 {
+                    let pairs = [
+                        (BigUint::from(a), BigUint::from(b)),
+                        (BigUint::from(b), BigUint::from(a)),
+                    ];
+                    let expected = a.pow(b as u32) % m * (b.pow(a as u32) % m) % m;
+                    let modulus = BigUint::from(m);
+                    assert_eq!(
+                        BigUint::multi_modpow(&pairs, &modulus),
+                        BigUint::from(expected)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_multi_modpow_zero() {
+        BigUint::multi_modpow(&[], &BigUint::from(0u32));
+    }
+
+    #[test]
+    fn test_crt_small() {
+        let big = |x: u32| BigUint::from(x);
+        assert_eq!(BigUint::crt(&[]), Some((big(0), big(1))));
+        // Exhaustively compare with a search for pairs of moduli.
+        for m1 in 1u32..16 {
+            for m2 in 1u32..16 {
+                let lcm = m1.lcm(&m2);
+                for r1 in 0..m1 {
+                    for r2 in 0..m2 {
+                        let x = (0..lcm).find(|x| x % m1 == r1 && x % m2 == r2);
+                        let pairs = [(big(r1), big(m1)), (big(r2 + m2), big(m2))];
+                        assert_eq!(BigUint::crt(&pairs), x.map(|x| (big(x), big(lcm))));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_crt_big() {
+        let b = BigUint::from_str_radix(super::BIG_B, 16).unwrap();
+        let m = BigUint::from_str_radix(super::BIG_M, 16).unwrap();
+        let moduli = [
+            (BigUint::one() << 127u8) - 1u32,
+            (BigUint::one() << 89u8) - 1u32,
+            BigUint::from(3u32).pow(50u32),
+            BigUint::one() << 64u8,
+            m.clone(),
+        ];
+        let product: BigUint = moduli.iter().product();
+        let x = &b % &product;
+
+        let pairs: Vec<_> = moduli.iter().map(|n| (&x % n, n.clone())).collect();
+        assert_eq!(BigUint::crt(&pairs), Some((x.clone(), product.clone())));
+
+        let basis = CrtBasis::new(&moduli).unwrap();
+        assert_eq!(basis.moduli(), &moduli[..]);
+        assert_eq!(*basis.product(), product);
+        let residues: Vec<_> = pairs.iter().map(|(r, _)| r.clone()).collect();
+        assert_eq!(basis.combine(&residues), x);
+
+        // Shared factors are fine for `crt` if the residues agree.
+        let shared = [(&x % &m, m.clone()), (&x % (&m * 3u32), &m * 3u32)];
+        assert_eq!(BigUint::crt(&shared), Some((&x % (&m * 3u32), &m * 3u32)));
+        let conflict = [(&x % &m, m.clone()), (&x % (&m * 3u32) + 1u32, &m * 3u32)];
+        assert_eq!(BigUint::crt(&conflict), None);
+        assert!(CrtBasis::new(&[m.clone(), &m * 3u32]).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_crt_zero() {
+        BigUint::crt(&[(BigUint::one(), BigUint::from(0u32))]);
+    }
+
+    #[test]
+    fn test_discrete_log_small() {
+        let big = |x: u32| BigUint::from(x);
+        for &p in &[2u32, 3, 5, 7, 11, 13, 101, 257, 1009] {
+            for g in 0..cmp::min(p, 20) {
+                // the least solutions, by exhaustive search
+                let mut expected = vec![None; p as usize];
+                let mut power = 1 % p;
+                for x in 0..p {
+                    let slot = &mut expected[power as usize];
+                    if slot.is_none() {
+                        *slot = Some(big(x));
+                    }
+                    power = power * g % p;
+                }
+                for h in 0..cmp::min(p, 50) {
+                    let expected = expected[h as usize].clone();
+                    let (h, g, big_p) = (big(h), big(g), big(p));
+                    assert_eq!(
+                        h.discrete_log(&g, &big_p),
+                        expected,
+                        "{} {} {}",
+                        h,
+                        g,
+                        big_p
+                    );
+                    if !g.is_zero() {
+                        assert_eq!(h.discrete_log_bsgs(&g, &big_p, &big_p), expected);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_discrete_log_big() {
+        let big = |x: u64| BigUint::from(x);
+
+        // p - 1 = 2^2 3^2 5 7 11 ... 43 is smooth, and 2 is a primitive root
+        let p = big(78_496_567_990_020_181);
+        let g = big(2);
+        let primes = [2u64, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43];
+        let order: Vec<_> = primes
+            .iter()
+            .map(|&q| (big(q), if q < 5 { 2 } else { 1 }))
+            .collect();
+        for &x in &[0u64, 1, 12345, 6_541_380_665_835_015] {
+            let h = g.modpow(&big(x), &p);
+            assert_eq!(h.discrete_log(&g, &p), Some(big(x)));
+            assert_eq!(h.discrete_log_pohlig_hellman(&g, &p, &order), Some(big(x)));
+        }
+
+        // a subgroup of 34-bit prime order q in the group modulo p = 2q + 1
+        let q = big(8_589_934_631);
+        let p: BigUint = &q * 2u32 + 1u32;
+        assert!(q.is_probably_prime(0) && p.is_probably_prime(0));
+        let g = big(4);
+        for &x in &[1u64, 7_654_321_098] {
+            let h = g.modpow(&big(x), &p);
+            assert_eq!(h.discrete_log_rho(&g, &p, &q), Some(big(x)));
+            assert_eq!(h.discrete_log(&g, &p), Some(big(x)));
+        }
+        // -1 is a non-residue for p = 3 (mod 4), so it's not a power of 4
+        let minus_one = &p - 1u32;
+        assert_eq!(minus_one.discrete_log_rho(&g, &p, &q), None);
+        assert_eq!(minus_one.discrete_log(&g, &p), None);
+
+        // the bound excludes the solution
+        let h = big(3).modpow(&big(1000), &p);
+        assert_eq!(
+            h.discrete_log_bsgs(&big(3), &p, &big(1001)),
+            Some(big(1000))
+        );
+        assert_eq!(h.discrete_log_bsgs(&big(3), &p, &big(1000)), None);
+    }
+
+    #[test]
+    fn test_modinv_small() {
+        for m in 1u32..50 {
+            let big_m = BigUint::from(m);
+            for a in 0u32..100 {
+                let big_a = BigUint::from(a);
+                match big_a.modinv(&big_m) {
+                    Some(inv) => {
+                        assert!(inv < big_m);
+                        assert_eq!((&big_a * &inv) % &big_m, BigUint::from(1 % m));
+                    }
+                    None => assert_ne!(a.gcd(&m), 1),
+                }
+            }
+        }
+        assert_eq!(BigUint::from(6u32).modinv(&BigUint::from(9u32)), None);
+    }
+
+    #[test]
+    fn test_modinv_big() {
+        let b = BigUint::from_str_radix(super::BIG_B, 16).unwrap();
+        let e = BigUint::from_str_radix(super::BIG_E, 16).unwrap();
+        let m = BigUint::from_str_radix(super::BIG_M, 16).unwrap();
+
+        // m is prime, so the inverse is also b^(m-2)
+        let two = BigUint::from(2u32);
+        for x in &[&b, &e, &(&b * &e)] {
+            let inv = x.modinv(&m).unwrap();
+            assert_eq!(inv, x.modpow(&(&m - &two), &m));
+            assert!(((*x * &inv) % &m).is_one());
+        }
+
+        let inv = b.modinv(&e).unwrap();
+        assert!(((&b * &inv) % &e).is_one());
+        assert_eq!((&b << 1u8).modinv(&(&e << 1u8)), None);
+    }
+
+    #[test]
+    fn test_montgomery_context() {
+        let b = BigUint::from_str_radix(super::BIG_B, 16).unwrap();
+        let e = BigUint::from_str_radix(super::BIG_E, 16).unwrap();
+        let m = BigUint::from_str_radix(super::BIG_M, 16).unwrap();
+        let r = BigUint::from_str_radix(super::BIG_R, 16).unwrap();
+
+        let ctx = MontgomeryContext::new(&m);
+        assert_eq!(ctx.modulus(), &m);
+        assert_eq!(ctx.modpow(&b, &e), r);
+
+        let x = ctx.to_monty(&b);
+        let y = ctx.to_monty(&e);
+        assert_eq!(ctx.from_monty(&x), &b % &m);
+        assert_eq!(ctx.from_monty(&ctx.pow(&x, &e)), r);
+        assert_eq!(ctx.from_monty(&ctx.mul(&x, &y)), &b * &e % &m);
+        assert_eq!(ctx.from_monty(&ctx.square(&x)), &b * &b % &m);
+        assert_eq!(ctx.square(&x), ctx.mul(&x, &x));
+        assert_eq!(ctx.mul(&x, &ctx.one()), x);
+        assert_eq!(ctx.pow(&x, &BigUint::from(0u32)), ctx.one());
+        assert_eq!(ctx.to_monty(&(&b + &m)), x);
+    }
+
+    #[test]
+    fn test_montgomery_context_small() {
+        for m in (1u32..40).filter(|m| m.is_odd()) {
+            let ctx = MontgomeryContext::new(&BigUint::from(m));
+            assert_eq!(ctx.from_monty(&ctx.one()), BigUint::from(1 % m));
+            for a in 0u32..40 {
+                let x = ctx.to_monty(&BigUint::from(a));
+                assert_eq!(ctx.from_monty(&x), BigUint::from(a % m));
+                for b in 0u32..10 {
+                    let y = ctx.to_monty(&BigUint::from(b));
+                    let xy = ctx.mul(&x, &y);
+                    assert_eq!(ctx.from_monty(&xy), BigUint::from(a * b % m));
+                    let xb = ctx.pow(&x, &BigUint::from(b));
+                    assert_eq!(
+                        ctx.from_monty(&xb),
+                        BigUint::from(u64::from(a).pow(b) % u64::from(m))
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_montgomery_context_even() {
+        MontgomeryContext::new(&BigUint::from(10u32));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_modinv_zero() {
+        BigUint::from(3u32).modinv(&BigUint::from(0u32));
+    }
+
+    #[test]
+    fn test_barrett_reducer() {
+        let b = BigUint::from_str_radix(super::BIG_B, 16).unwrap();
+        let e = BigUint::from_str_radix(super::BIG_E, 16).unwrap();
+        let m = BigUint::from_str_radix(super::BIG_M, 16).unwrap();
+        let r = BigUint::from_str_radix(super::BIG_R, 16).unwrap();
+
+        let reducer = BarrettReducer::new(&m);
+        assert_eq!(reducer.modulus(), &m);
+        assert_eq!(reducer.pow_mod(&b, &e), r);
+        assert_eq!(reducer.mul_mod(&b, &e), &b * &e % &m);
+
+        for modulus in &[m.clone(), &m << 1u8, &m >> 100u8, BigUint::from(1u32)] {
+            let reducer = BarrettReducer::new(modulus);
+            for x in &[
+                BigUint::zero(),
+                modulus - 1u32,
+                modulus.clone(),
+                modulus * modulus - 1u32,
+                &b * &e,
+                &b * &e * &e,
+            ] {
+                assert_eq!(reducer.reduce(x), x % modulus);
+            }
+            let even_b = &b << 1u8;
+            assert_eq!(reducer.pow_mod(&even_b, &e), even_b.modpow(&e, modulus));
+        }
+    }
+
+    #[test]
+    fn test_barrett_reducer_small() {
+        for m in 1u32..40 {
+            let reducer = BarrettReducer::new(&BigUint::from(m));
+            for a in 0u32..40 {
+                let x = BigUint::from(a);
+                assert_eq!(reducer.reduce(&x), BigUint::from(a % m));
+                for b in 0u32..10 {
+                    let y = BigUint::from(b);
+                    assert_eq!(reducer.mul_mod(&x, &y), BigUint::from(a * b % m));
+                    let expected = u64::from(a).pow(b) % u64::from(m);
+                    assert_eq!(reducer.pow_mod(&x, &y), BigUint::from(expected));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_barrett_reducer_zero() {
+        BarrettReducer::new(&BigUint::from(0u32));
+    }
+
+    #[test]
+    fn test_fixed_uint() {
+        let x = FixedUint::from_bytes_be(&[0, 0, 1, 2, 3]);
+        assert_eq!(x.bits(), 40);
+        assert_eq!(x.to_biguint(), BigUint::from(0x01_02_03u32));
+        assert_eq!(x.to_bytes_be(), [0, 0, 1, 2, 3]);
+        assert_eq!(x.to_bytes_le(), [3, 2, 1, 0, 0]);
+        assert!(x.ct_eq(&FixedUint::from_bytes_le(&[3, 2, 1, 0, 0])));
+        assert!(x.ct_eq(&FixedUint::from_biguint(&BigUint::from(0x01_02_03u32), 100)));
+
+        let y = FixedUint::from_biguint(&BigUint::from(0x01_02_04u32), 40);
+        assert!(!x.ct_eq(&y));
+        assert!(FixedUint::ct_select(&x, &y, true).ct_eq(&y));
+        assert!(FixedUint::ct_select(&x, &y, false).ct_eq(&x));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fixed_uint_overflow() {
+        FixedUint::from_biguint(&BigUint::from(256u32), 8);
+    }
+
+    #[test]
+    fn test_constant_time_big() {
+        let b = BigUint::from_str_radix(super::BIG_B, 16).unwrap();
+        let e = BigUint::from_str_radix(super::BIG_E, 16).unwrap();
+        let m = BigUint::from_str_radix(super::BIG_M, 16).unwrap();
+        let r = BigUint::from_str_radix(super::BIG_R, 16).unwrap();
+
+        let ctx = ConstantTimeContext::new(&m);
+        assert_eq!(ctx.modulus(), &m);
+        let fixed_b = FixedUint::from_biguint(&b, 2048);
+        let fixed_e = FixedUint::from_biguint(&e, 2048);
+        let result = ctx.modpow(&fixed_b, &fixed_e);
+        assert_eq!(result.bits(), m.bits());
+        assert_eq!(result.to_biguint(), r);
+
+        let product = ctx.mul(&fixed_b, &fixed_e);
+        assert_eq!(product.to_biguint(), &b * &e % &m);
+        let inv = ctx.modinv(&fixed_b).unwrap();
+        assert_eq!(inv.to_biguint(), b.modinv(&m).unwrap());
+
+        // A base wider than the modulus is reduced first.
+        let wide = &b * &e * &m + 12345u32;
+        let fixed_wide = FixedUint::from_biguint(&wide, 8192);
+        let small = FixedUint::from_biguint(&BigUint::from(7u32), 3);
+        assert_eq!(
+            ctx.modpow(&fixed_wide, &small).to_biguint(),
+            BigUint::from(12345u32).pow(7) % &m
+        );
+        assert_eq!(
+            ctx.modinv(&fixed_wide).unwrap().to_biguint(),
+            BigUint::from(12345u32).modinv(&m).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_constant_time_uneven() {
+        // widths that aren't a multiple of the modulus length
+        let m = (BigUint::one() << 127u8) + 45u32;
+        let ctx = ConstantTimeContext::new(&m);
+        let one = FixedUint::from_biguint(&BigUint::one(), 1);
+        for &(bits, width) in &[
+            (190u64, 192u64),
+            (130, 150),
+            (64, 72),
+            (255, 320),
+            (300, 520),
+        ] {
+            let x = (BigUint::one() << bits) + 987_654_321u32;
+            let fixed_x = FixedUint::from_biguint(&x, width);
+            assert_eq!(ctx.mul(&fixed_x, &one).to_biguint(), &x % &m);
+            assert_eq!(ctx.mul(&one, &fixed_x).to_biguint(), &x % &m);
+
+            let e = FixedUint::from_biguint(&BigUint::from(65537u32), 24);
+            let expected = x.modpow(&BigUint::from(65537u32), &m);
+            assert_eq!(ctx.modpow(&fixed_x, &e).to_biguint(), expected);
+            let inv = ctx.modinv(&fixed_x).map(|v| v.to_biguint());
+            assert_eq!(inv, x.modinv(&m));
+        }
+    }
+
+    #[test]
+    fn test_constant_time_small() {
+        for m in (1u32..60).filter(|m| m.is_odd()) {
+            let big_m = BigUint::from(m);
+            let ctx = ConstantTimeContext::new(&big_m);
+            for a in 0u32..60 {
+                let big_a = BigUint::from(a);
+                let x = FixedUint::from_biguint(&big_a, 16);
+                assert_eq!(ctx.modinv(&x).map(|v| v.to_biguint()), big_a.modinv(&big_m));
+                for b in 0u32..12 {
+                    let big_b = BigUint::from(b);
+                    let y = FixedUint::from_biguint(&big_b, 8);
+                    assert_eq!(
+                        ctx.modpow(&x, &y).to_biguint(),
+                        big_a.modpow(&big_b, &big_m)
+                    );
+                    assert_eq!(ctx.mul(&x, &y).to_biguint(), BigUint::from(a * b % m));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_constant_time_secret_modulus() {
+        // The modulus may be secret, so it isn't printed, and each clone has
+        // its own copy to wipe.
+        let m = BigUint::from_str_radix(super::BIG_M, 16).unwrap();
+        let ctx = ConstantTimeContext::new(&m);
+        let debug = format!("{:?}", ctx);
+        assert_eq!(
+            debug,
+            format!("ConstantTimeContext {{ bits: {} }}", m.bits())
+        );
+
+        let copy = ctx.clone();
+        drop(ctx);
+        assert_eq!(copy.modulus(), &m);
+        let x = FixedUint::from_biguint(&BigUint::from(12345u32), 16);
+        assert_eq!(
+            copy.mul(&x, &x).to_biguint(),
+            BigUint::from(12345u32).pow(2) % &m
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_constant_time_even() {
+        ConstantTimeContext::new(&BigUint::from(10u32));
+    }
 }
 
 mod bigint {
@@ -178,4 +752,40 @@ mod bigint {
 
         check_modpow(b, e, m, r);
     }
+
+    #[test]
+    fn test_modinv_small() {
+        for m in -30i32..31 {
+            if m == 0 {
+                continue;
+            }
+            let big_m = BigInt::from(m);
+            for a in -30i32..31 {
+                let big_a = BigInt::from(a);
+                match big_a.modinv(&big_m) {
+                    Some(inv) => {
+                        // the sign of the result follows the modulus like `mod_floor`
+                        assert_eq!(inv.mod_floor(&big_m), inv);
+                        assert_eq!(
+                            (&big_a * &inv).mod_floor(&big_m),
+                            BigInt::one().mod_floor(&big_m)
+                        );
+                    }
+                    None => assert_ne!(a.gcd(&m), 1),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_modinv_big() {
+        let b = BigInt::from_str_radix(super::BIG_B, 16).unwrap();
+        let m = BigInt::from_str_radix(super::BIG_M, 16).unwrap();
+
+        // m is prime, so the inverse is also b^(m-2)
+        let exp = &m - BigInt::from(2);
+        assert_eq!(b.modinv(&m).unwrap(), b.modpow(&exp, &m));
+        assert_eq!((-&b).modinv(&m).unwrap(), (-&b).modpow(&exp, &m));
+        assert_eq!(b.modinv(&-&m).unwrap(), b.modpow(&exp, &-&m));
+    }
 }
@@ -0,0 +1,189 @@
+use num_bigint::{BigInt, BigUint, Factorizer};
+use num_traits::{One, Pow};
+
+fn mersenne(p: u32) -> BigUint {
+    (BigUint::one() << p) - 1u32
+}
+
+fn check(n: &BigUint, expected: bool) {
+    for &rounds in &[0, 1, 10] {
+        assert_eq!(n.is_probably_prime(rounds), expected, "{}", n);
+    }
+}
+
+#[test]
+fn test_is_probably_prime_small() {
+    let primes = [2u32, 3, 5, 7, 1021, 1031, 65521, 1048573, 2147483647];
+    for &p in &primes {
+        check(&BigUint::from(p), true);
+    }
+    let composites = [0u32, 1, 4, 9, 561, 1024, 1042441, 1048576, 41041, 825265];
+    for &n in &composites {
+        check(&BigUint::from(n), false);
+    }
+
+    let u64_primes = [18446744073709551557u64, 1000000000000000003, 4294967311];
+    for &p in &u64_primes {
+        check(&BigUint::from(p), true);
+    }
+}
+
+#[test]
+fn test_is_probably_prime_pseudoprimes() {
+    // strong pseudoprimes to the first few prime bases
+    let pseudoprimes = [
+        "2047",
+        "3215031751",
+        "3825123056546413051",
+        "318665857834031151167461",
+        "3317044064679887385961981",
+    ];
+    for n in &pseudoprimes {
+        check(&n.parse().unwrap(), false);
+    }
+
+    // a Carmichael number (6k + 1)(12k + 1)(18k + 1)
+    let carmichael = "1296000004358844004886708077826165821249".parse().unwrap();
+    check(&carmichael, false);
+}
+
+#[test]
+fn test_is_probably_prime_big() {
+    for &p in &[61, 89, 107, 127, 521, 607, 1279] {
+        check(&mersenne(p), true);
+    }
+    for &p in &[67, 101, 257, 1277] {
+        check(&mersenne(p), false);
+    }
+
+    check(&"18446744073709551629".parse().unwrap(), true);
+    check(&((BigUint::one() << 255u32) - 19u32), true);
+    let goldilocks = (BigUint::one() << 448u32) - (BigUint::one() << 224u32) - 1u32;
+    check(&goldilocks, true);
+
+    check(&(mersenne(127) * mersenne(89)), false);
+    check(&mersenne(127).pow(2u32), false);
+    check(&(mersenne(61) << 1u32), false);
+    check(&(mersenne(521) * 1021u32), false);
+}
+
+#[test]
+fn test_is_probably_prime_bigint() {
+    let p = BigInt::from(mersenne(127));
+    assert!(p.is_probably_prime(0));
+    assert!(!(-&p).is_probably_prime(0));
+    assert!(!BigInt::from(0).is_probably_prime(0));
+    assert!(!BigInt::from(-7).is_probably_prime(0));
+    assert!(BigInt::from(7).is_probably_prime(0));
+}
+
+#[test]
+fn test_next_prev_prime() {
+    let two = BigUint::from(2u32);
+    assert_eq!(BigUint::from(0u32).next_prime(), two);
+    assert_eq!(BigUint::from(1u32).next_prime(), two);
+    assert_eq!(two.next_prime(), BigUint::from(3u32));
+    assert_eq!(BigUint::from(0u32).prev_prime(), None);
+    assert_eq!(two.prev_prime(), None);
+    assert_eq!(BigUint::from(3u32).prev_prime(), Some(two));
+
+    // a maximal prime gap
+    let p = BigUint::from(1693182318746371u64);
+    let q = &p + 1132u32;
+    assert_eq!(p.next_prime(), q);
+    assert_eq!((&p + 1u32).next_prime(), q);
+    assert_eq!(q.prev_prime(), Some(p.clone()));
+    assert_eq!((&q - 1u32).prev_prime(), Some(p));
+
+    let n = BigUint::one() << 64u32;
+    assert_eq!(n.next_prime(), &n + 13u32);
+    assert_eq!(n.prev_prime(), Some(&n - 59u32));
+
+    let n = BigUint::from(10u32).pow(100u32);
+    assert_eq!(n.next_prime(), &n + 267u32);
+    assert_eq!(n.prev_prime(), Some(&n - 797u32));
+
+    let n = BigUint::one() << 512u32;
+    assert_eq!(n.next_prime(), &n + 75u32);
+    assert_eq!(n.prev_prime(), Some(&n - 569u32));
+
+    assert_eq!(
+        mersenne(127).prev_prime().unwrap().next_prime(),
+        mersenne(127)
+    );
+    assert_eq!((mersenne(127) + 1u32).prev_prime(), Some(mersenne(127)));
+}
+
+fn product(factors: &[(BigUint, u32)]) -> BigUint {
+    factors.iter().map(|(p, k)| p.pow(*k)).product()
+}
+
+#[test]
+fn test_factor_small() {
+    assert!(BigUint::one().factor().factors().is_empty());
+    for n in 2u32..5000 {
+        let factorization = BigUint::from(n).factor();
+        assert!(factorization.is_complete());
+        let factors = factorization.factors();
+        assert_eq!(product(factors), BigUint::from(n));
+        assert!(factors.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(factors.iter().all(|(p, _)| p.is_probably_prime(0)));
+    }
+}
+
+#[test]
+fn test_factor_big() {
+    let cases = [
+        // 2^64 + 1
+        vec![(274177u64, 1), (67280421310721, 1)],
+        vec![(2, 5), (3, 1), (1_000_003, 2), (2_147_483_647, 1)],
+        vec![(4099, 3), (1_000_000_007, 2), (1_099_511_627_791, 1)],
+    ];
+    for factors in &cases {
+        let factors: Vec<_> = factors
+            .iter()
+            .map(|&(p, k)| (BigUint::from(p), k))
+            .collect();
+        let n = product(&factors);
+        let factorization = n.factor();
+        assert!(factorization.is_complete(), "{}", n);
+        assert_eq!(factorization.factors(), &factors[..]);
+    }
+
+    // a perfect power of a semiprime
+    let n = (mersenne(61) * 1_000_000_007u32).pow(3u32);
+    let factorization = n.factor();
+    assert_eq!(
+        factorization.factors(),
+        &[(BigUint::from(1_000_000_007u32), 3), (mersenne(61), 3)][..]
+    );
+}
+
+#[test]
+fn test_factor_partial() {
+    let p = mersenne(61);
+    let q = mersenne(89);
+    let n = (&p * &q) * 3u32 * 3u32 * 5u32;
+
+    // trial division alone only finds the small factors
+    let limited = Factorizer::new()
+        .trial_division(100)
+        .pollard_rho(0)
+        .pollard_pm1(0)
+        .ecm(0, 0);
+    let factorization = limited.factor(&n);
+    assert!(!factorization.is_complete());
+    let small = [(BigUint::from(3u32), 2), (BigUint::from(5u32), 1)];
+    assert_eq!(factorization.factors(), &small[..]);
+    assert_eq!(factorization.cofactors(), &[(&p * &q, 1)][..]);
+
+    // nothing at all, even for small factors
+    let factorization = limited.trial_division(0).factor(&BigUint::from(15u32));
+    assert_eq!(factorization.cofactors(), &[(BigUint::from(15u32), 1)][..]);
+}
+
+#[test]
+#[should_panic]
+fn test_factor_zero() {
+    BigUint::from(0u32).factor();
+}
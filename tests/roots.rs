@@ -16,6 +16,11 @@ mod biguint {
 
         let lo = root.pow(n);
         assert!(lo <= x);
+        assert_eq!(x.nth_root_rem(n), (root.clone(), &x - &lo));
+        if n == 2 {
+            assert_eq!(x.sqrt_rem(), (root.clone(), &x - &lo));
+            assert_eq!(x.is_perfect_square(), x == lo);
+        }
         assert_eq!(lo.nth_root(n), root);
         if !lo.is_zero() {
             assert_eq!((&lo - 1u32).nth_root(n), &root - 1u32);
@@ -110,6 +115,58 @@ mod biguint {
         check(x.clone(), 10);
         check(x, 100);
     }
+
+    #[test]
+    fn test_is_perfect_square() {
+        for x in 0u32..10_000 {
+            let root = (x as f64).sqrt() as u32;
+            let expected = root * root == x;
+            assert_eq!(BigUint::from(x).is_perfect_square(), expected, "{}", x);
+        }
+
+        let x = BigUint::from(3u32).pow(1001) << 20u8;
+        assert!(!x.is_perfect_square());
+        assert!((&x * 3u32).is_perfect_square());
+        assert!(!(&x * 12u32 + 1u32).is_perfect_square());
+    }
+
+    #[test]
+    fn test_is_perfect_power() {
+        let big = |x: u32| BigUint::from(x);
+        assert_eq!(big(0).is_perfect_power(), None);
+        assert_eq!(big(1).is_perfect_power(), None);
+        assert_eq!(big(2).is_perfect_power(), None);
+        assert_eq!(big(4).is_perfect_power(), Some((big(2), 2)));
+        assert_eq!(big(64).is_perfect_power(), Some((big(2), 6)));
+        assert_eq!(big(72).is_perfect_power(), None);
+        assert_eq!(big(1 << 31).is_perfect_power(), Some((big(2), 31)));
+        assert_eq!(big(3u32.pow(20)).is_perfect_power(), Some((big(3), 20)));
+        assert_eq!(big(36u32.pow(6)).is_perfect_power(), Some((big(6), 12)));
+
+        // Compare with all the small powers.
+        let mut powers = std::collections::BTreeMap::new();
+        for base in 2u32..400 {
+            let mut x = u64::from(base) * u64::from(base);
+            let mut exp = 2;
+            while x < 100_000 {
+                powers.entry(x).or_insert((base, exp));
+                x *= u64::from(base);
+                exp += 1;
+            }
+        }
+        for x in 0u64..100_000 {
+            let expected = powers.get(&x).map(|&(b, e)| (big(b), e));
+            assert_eq!(BigUint::from(x).is_perfect_power(), expected, "{}", x);
+        }
+
+        let base = BigUint::from(10u32).pow(30) + 7u32;
+        for &exp in &[2u32, 3, 5, 12, 49] {
+            let x = base.pow(exp);
+            assert_eq!(x.is_perfect_power(), Some((base.clone(), exp)));
+            assert_eq!((&x + 1u32).is_perfect_power(), None);
+            assert_eq!((x - 1u32).is_perfect_power(), None);
+        }
+    }
 }
 
 mod bigint {